@@ -0,0 +1,486 @@
+use crate::audio;
+use crate::rpc::RpcEvent;
+use crate::types::{AlignTranscriptParams, AlignTranscriptResult, CaptionSegment, ExtractAudioParams, TranscribeSegmentsParams, TranscriptionTask, WordSpan};
+use std::path::Path;
+
+// ---- Alignment tuning ----
+const MATCH_COST: u32 = 0;
+const NEAR_MATCH_COST: u32 = 1;                // small spelling differences ("colour" / "color")
+const SUBSTITUTE_COST: u32 = 3;                // cheaper than a gap pair, so mismatches stay in step
+const GAP_COST: u32 = 2;
+const MAX_ALIGNMENT_CELLS: usize = 50_000_000; // one byte of backtrack per cell
+const MIN_WORD_MS: u64 = 80;                   // interpolated words never get less than this
+const AVG_WORD_MS: u64 = 300;                  // pace assumed before the first / after the last match
+const MAX_SEGMENT_WORDS: usize = 12;
+const SEGMENT_PAUSE_MS: u64 = 1000;            // a pause this long starts a new segment
+const SCRIPT_PROMPT_CHARS: usize = 600;
+
+/// One word of the script: the exact text to show and the form used for matching
+struct ScriptWord {
+    text: String,
+    key: String,
+    breaks_after: bool,
+}
+
+/// Lowercased letters and digits only, so "Don't," matches "dont" and "$1,500" matches "1500"
+fn match_key(token: &str) -> String {
+    token.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn ends_sentence(token: &str) -> bool {
+    token.trim_end_matches(['"', '\'', '”', '’', ')', ']', '»'])
+        .ends_with(['.', '!', '?', '…'])
+}
+
+/// Split the script into words, keeping punctuation attached and remembering sentence and line ends
+fn tokenize_script(script: &str) -> Vec<ScriptWord> {
+    let mut words: Vec<ScriptWord> = Vec::new();
+    let mut leading = String::new();
+
+    for line in script.lines() {
+        for token in line.split_whitespace() {
+            let key = match_key(token);
+            if key.is_empty() {
+                // Lone punctuation ("—", "&") sticks to a neighbour instead of becoming a word
+                match words.last_mut() {
+                    Some(prev) => {
+                        prev.text.push(' ');
+                        prev.text.push_str(token);
+                        prev.breaks_after |= ends_sentence(token);
+                    }
+                    None => {
+                        leading.push_str(token);
+                        leading.push(' ');
+                    }
+                }
+                continue;
+            }
+            let text = format!("{}{}", std::mem::take(&mut leading), token);
+            words.push(ScriptWord { text, key, breaks_after: ends_sentence(token) });
+        }
+        if let Some(last) = words.last_mut() {
+            last.breaks_after = true;
+        }
+    }
+    words
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != cb);
+            row[j + 1] = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+fn pair_cost(script: &str, heard: &str) -> u32 {
+    if script == heard {
+        return MATCH_COST;
+    }
+    let (a, b): (Vec<char>, Vec<char>) = (script.chars().collect(), heard.chars().collect());
+    let longest = a.len().max(b.len());
+    if longest >= 4 && a.len().abs_diff(b.len()) <= longest / 4 && levenshtein(&a, &b) <= longest / 4 {
+        NEAR_MATCH_COST
+    } else {
+        SUBSTITUTE_COST
+    }
+}
+
+/// A script word paired with a recognized word by the alignment
+#[derive(Clone, Copy)]
+struct Pairing {
+    first: usize, // recognized words first..=last (whisper may split one word into several)
+    last: usize,
+    exact: bool, // same word (allowing small spelling differences) rather than a mishearing
+}
+
+/// Global word-sequence alignment (Needleman-Wunsch). For each script word, returns the
+/// recognized word it lines up with, or None when it has to be interpolated.
+fn align_words(script: &[&str], heard: &[&str]) -> anyhow::Result<Vec<Option<Pairing>>> {
+    const DIAG: u8 = 0;
+    const UP: u8 = 1;   // script word with no recognized counterpart
+    const LEFT: u8 = 2; // recognized word that is not in the script
+
+    let (n, m) = (script.len(), heard.len());
+    let width = m + 1;
+    let cells = (n + 1).saturating_mul(width);
+    if cells > MAX_ALIGNMENT_CELLS {
+        return Err(anyhow::anyhow!("Script ({} words) is too long to align against {} recognized words in one pass", n, m));
+    }
+
+    let mut steps = vec![DIAG; cells];
+    let mut prev: Vec<u32> = (0..width as u32).map(|j| j * GAP_COST).collect();
+    let mut row = vec![0u32; width];
+    steps[1..width].fill(LEFT);
+
+    for i in 1..=n {
+        row[0] = i as u32 * GAP_COST;
+        steps[i * width] = UP;
+        for j in 1..=m {
+            let diag = prev[j - 1] + pair_cost(script[i - 1], heard[j - 1]);
+            let up = prev[j] + GAP_COST;
+            let left = row[j - 1] + GAP_COST;
+            let (cost, step) = if diag <= up && diag <= left {
+                (diag, DIAG)
+            } else if up <= left {
+                (up, UP)
+            } else {
+                (left, LEFT)
+            };
+            row[j] = cost;
+            steps[i * width + j] = step;
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+
+    let mut matches = vec![None; n];
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        match steps[i * width + j] {
+            DIAG => {
+                // A misheard word sits between matches, so whisper's timing for it is still good
+                let exact = pair_cost(script[i - 1], heard[j - 1]) < SUBSTITUTE_COST;
+                matches[i - 1] = Some(Pairing { first: j - 1, last: j - 1, exact });
+                i -= 1;
+                j -= 1;
+            }
+            UP => i -= 1,
+            _ => j -= 1,
+        }
+    }
+
+    join_split_words(script, heard, &mut matches);
+    Ok(matches)
+}
+
+/// A mishearing next to leftover recognized words is often one word split in two
+/// ("CapSlap" heard as "cap" + "slap"): claim the neighbours while they spell part of the script word
+fn join_split_words(script: &[&str], heard: &[&str], matches: &mut [Option<Pairing>]) {
+    let mut claimed = vec![false; heard.len()];
+    for p in matches.iter().flatten() {
+        claimed[p.first..=p.last].fill(true);
+    }
+
+    for (key, pairing) in script.iter().zip(matches.iter_mut()) {
+        let Some(p) = pairing.as_mut().filter(|p| !p.exact) else { continue };
+        let spelled = |first: usize, last: usize| heard[first..=last].concat();
+
+        while p.first > 0 && !claimed[p.first - 1] && key.contains(&spelled(p.first - 1, p.last)) {
+            p.first -= 1;
+            claimed[p.first] = true;
+        }
+        while p.last + 1 < heard.len() && !claimed[p.last + 1] && key.contains(&spelled(p.first, p.last + 1)) {
+            p.last += 1;
+            claimed[p.last] = true;
+        }
+        p.exact = pair_cost(key, &spelled(p.first, p.last)) < SUBSTITUTE_COST;
+    }
+}
+
+/// Fill in times for unpaired script words, spreading each run between its neighbouring
+/// pairs by character count. Runs with no room borrow time from the matches around them.
+fn interpolate_times(
+    anchors: Vec<Option<(u64, u64)>>,
+    lengths: &[usize],
+    speech: (u64, u64),
+    duration_ms: Option<u64>,
+) -> Vec<(u64, u64)> {
+    let n = anchors.len();
+    let mut times = anchors;
+    let mut i = 0;
+
+    while i < n {
+        if times[i].is_some() {
+            i += 1;
+            continue;
+        }
+        let gap_start = i;
+        while i < n && times[i].is_none() {
+            i += 1;
+        }
+        let gap_end = i;
+        let count = (gap_end - gap_start) as u64;
+        let prev = gap_start.checked_sub(1);
+        let next = (gap_end < n).then_some(gap_end);
+
+        let (mut from, mut to) = match (prev.and_then(|p| times[p]), next.and_then(|q| times[q])) {
+            (Some((_, prev_end)), Some((next_start, _))) => (prev_end, next_start.max(prev_end)),
+            (None, Some((next_start, _))) => (speech.0.min(next_start.saturating_sub(count * AVG_WORD_MS)), next_start),
+            (Some((_, prev_end)), None) => {
+                let end = speech.1.max(prev_end + count * AVG_WORD_MS);
+                (prev_end, duration_ms.map_or(end, |d| end.min(d).max(prev_end)))
+            }
+            (None, None) => speech,
+        };
+
+        let mut deficit = (count * MIN_WORD_MS).saturating_sub(to.saturating_sub(from));
+        if let Some(p) = prev.filter(|_| deficit > 0) {
+            if let Some((start, end)) = times[p] {
+                let share = if next.is_some() { deficit.div_ceil(2) } else { deficit };
+                let give = share.min(end.saturating_sub(start + MIN_WORD_MS));
+                times[p] = Some((start, end - give));
+                from -= give;
+                deficit -= give;
+            }
+        }
+        if let Some(q) = next.filter(|_| deficit > 0) {
+            if let Some((start, end)) = times[q] {
+                let give = deficit.min(end.saturating_sub(start + MIN_WORD_MS));
+                times[q] = Some((start + give, end));
+                to += give;
+            }
+        }
+
+        let total = lengths[gap_start..gap_end].iter().sum::<usize>().max(1) as u64;
+        let span = to.saturating_sub(from);
+        let mut cursor = from;
+        let mut chars = 0u64;
+        for k in gap_start..gap_end {
+            chars += lengths[k] as u64;
+            let end = from + span * chars / total;
+            times[k] = Some((cursor, end));
+            cursor = end;
+        }
+    }
+
+    times.into_iter().map(|t| t.unwrap_or_default()).collect()
+}
+
+/// Group timed script words into caption segments at sentence ends, line breaks and long pauses
+fn build_segments(words: &[ScriptWord], times: &[(u64, u64)], split_by_words: bool, language: Option<String>) -> Vec<CaptionSegment> {
+    if split_by_words {
+        return words.iter().zip(times)
+            .map(|(w, &(start_ms, end_ms))| CaptionSegment {
+                start_ms,
+                end_ms,
+                text: w.text.clone(),
+                words: Vec::new(),
+                language: language.clone(),
+                speaker: None,
+                confidence: None,
+                hidden: false,
+            })
+            .collect();
+    }
+
+    let mut segments = Vec::new();
+    let mut current: Vec<WordSpan> = Vec::new();
+    for (k, (w, &(start_ms, end_ms))) in words.iter().zip(times).enumerate() {
+        current.push(WordSpan { start_ms, end_ms, text: w.text.clone(), speaker: None, confidence: None, hidden: false });
+
+        let pause_follows = times.get(k + 1).is_some_and(|(next_start, _)| next_start.saturating_sub(end_ms) >= SEGMENT_PAUSE_MS);
+        if w.breaks_after || pause_follows || current.len() >= MAX_SEGMENT_WORDS || k + 1 == words.len() {
+            let spans = std::mem::take(&mut current);
+            segments.push(CaptionSegment {
+                start_ms: spans[0].start_ms,
+                end_ms: spans[spans.len() - 1].end_ms,
+                text: spans.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+                words: spans,
+                language: language.clone(),
+                speaker: None,
+                confidence: None,
+                hidden: false,
+            });
+        }
+    }
+    segments
+}
+
+/// Time the user's own script against the audio: whisper supplies word timings, the script
+/// supplies the words. Every script word is kept verbatim; words whisper missed or misheard
+/// are interpolated between the nearest matched words.
+pub async fn align_transcript(id: &str, p: AlignTranscriptParams, mut emit: impl FnMut(RpcEvent) + Send) -> anyhow::Result<AlignTranscriptResult> {
+    let script_words = tokenize_script(&p.script);
+    if script_words.is_empty() {
+        return Err(anyhow::anyhow!("Script is empty"));
+    }
+
+    let temp_dir = std::env::temp_dir().join(format!("capslap_align_{}", id));
+    tokio::fs::create_dir_all(&temp_dir).await?;
+    let result = align_in_dir(id, p, script_words, &temp_dir, &mut emit).await;
+    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    result
+}
+
+async fn align_in_dir(
+    id: &str,
+    p: AlignTranscriptParams,
+    script_words: Vec<ScriptWord>,
+    temp_dir: &Path,
+    mut emit: impl FnMut(RpcEvent) + Send,
+) -> anyhow::Result<AlignTranscriptResult> {
+    let audio_params = ExtractAudioParams {
+        input: p.input.clone(),
+        codec: Some("mp3".to_string()),
+        out: Some(temp_dir.join(format!("audio_{}.mp3", id)).to_string_lossy().to_string()),
+    };
+    let audio_result = audio::extract_audio(id, audio_params, &mut emit).await?;
+
+    // The start of the script makes a good prompt: it carries the names and spelling we expect
+    let full_text = p.script.split_whitespace().collect::<Vec<_>>().join(" ");
+    let prompt: String = full_text.chars().take(SCRIPT_PROMPT_CHARS).collect();
+
+    let transcribe_params = TranscribeSegmentsParams {
+        audio: audio_result.audio,
+        model: p.model,
+        language: p.language,
+        split_by_words: true,
+        api_key: p.api_key,
+        prompt: Some(prompt),
+        video_file: Some(p.input),
+        task: TranscriptionTask::Transcribe,
+        project: p.project,
+        backend: p.backend,
+        backend_preference: p.backend_preference,
+        api_endpoint: p.api_endpoint,
+        diarize: false,
+    };
+    let temp_path = temp_dir.to_path_buf();
+    let transcription = crate::whisper::transcribe_segments_with_temp(id, transcribe_params, Some(&temp_path), &mut emit).await?;
+
+    let heard = &transcription.segments;
+    if heard.is_empty() {
+        return Err(anyhow::anyhow!("No speech was recognized in the audio, nothing to align the script to"));
+    }
+
+    let script_keys: Vec<&str> = script_words.iter().map(|w| w.key.as_str()).collect();
+    let heard_keys: Vec<String> = heard.iter().map(|w| match_key(&w.text)).collect();
+    let heard_keys: Vec<&str> = heard_keys.iter().map(String::as_str).collect();
+    let pairings = align_words(&script_keys, &heard_keys)?;
+
+    let matched_words = pairings.iter().flatten().filter(|p| p.exact).count();
+    let total_words = script_words.len();
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Matched {}/{} script words to {} recognized words, interpolating the rest",
+            matched_words, total_words, heard.len())
+    });
+    if matched_words * 3 < total_words {
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: "Less than a third of the script was recognized - check that the script belongs to this recording".into()
+        });
+    }
+
+    let anchors = pairings.iter()
+        .map(|p| p.map(|p| (heard[p.first].start_ms, heard[p.last].end_ms)))
+        .collect();
+    let lengths: Vec<usize> = script_words.iter().map(|w| w.key.chars().count()).collect();
+    let speech = (heard[0].start_ms, heard[heard.len() - 1].end_ms);
+    let duration_ms = transcription.duration.map(|d| (d * 1000.0) as u64);
+    let times = interpolate_times(anchors, &lengths, speech, duration_ms);
+
+    let segments = build_segments(&script_words, &times, p.split_by_words, transcription.language.clone());
+
+    Ok(AlignTranscriptResult {
+        segments,
+        full_text,
+        duration: transcription.duration,
+        language: transcription.language,
+        matched_words,
+        total_words,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(words: &[ScriptWord]) -> Vec<&str> {
+        words.iter().map(|w| w.key.as_str()).collect()
+    }
+
+    #[test]
+    fn tokenize_keeps_punctuation_and_marks_breaks() {
+        let words = tokenize_script("Don't stop — ever.\nNext line");
+        assert_eq!(keys(&words), ["dont", "stop", "ever", "next", "line"]);
+        assert_eq!(words[1].text, "stop —");
+        assert!(!words[1].breaks_after);
+        assert!(words[2].breaks_after);
+        assert!(words[4].breaks_after);
+    }
+
+    #[test]
+    fn tokenize_attaches_leading_punctuation_to_first_word() {
+        let words = tokenize_script("“ Hello there");
+        assert_eq!(words[0].text, "“ Hello");
+        assert_eq!(words.len(), 2);
+    }
+
+    #[test]
+    fn align_exact_sequence() {
+        let script = ["the", "quick", "fox"];
+        let pairings = align_words(&script, &script).unwrap();
+        for (i, p) in pairings.iter().enumerate() {
+            let p = p.unwrap();
+            assert_eq!((p.first, p.last, p.exact), (i, i, true));
+        }
+    }
+
+    #[test]
+    fn align_skips_missing_and_extra_words() {
+        let script = ["we", "really", "like", "cats"];
+        let heard = ["we", "like", "um", "cats"];
+        let pairings = align_words(&script, &heard).unwrap();
+        assert_eq!(pairings[0].map(|p| p.first), Some(0));
+        assert!(pairings[1].is_none());
+        assert_eq!(pairings[2].map(|p| p.first), Some(1));
+        assert_eq!(pairings[3].map(|p| p.first), Some(3));
+    }
+
+    #[test]
+    fn align_accepts_near_spellings() {
+        let pairings = align_words(&["colour"], &["color"]).unwrap();
+        assert!(pairings[0].unwrap().exact);
+    }
+
+    #[test]
+    fn align_joins_split_words() {
+        let script = ["try", "capslap", "today"];
+        let heard = ["try", "cap", "slap", "today"];
+        let pairings = align_words(&script, &heard).unwrap();
+        let p = pairings[1].unwrap();
+        assert_eq!((p.first, p.last, p.exact), (1, 2, true));
+        assert_eq!(pairings[2].map(|p| p.first), Some(3));
+    }
+
+    #[test]
+    fn interpolate_spreads_gap_by_length() {
+        let anchors = vec![Some((0, 100)), None, None, Some((700, 800))];
+        let times = interpolate_times(anchors, &[1, 2, 4, 1], (0, 800), None);
+        assert_eq!(times, [(0, 100), (100, 300), (300, 700), (700, 800)]);
+    }
+
+    #[test]
+    fn interpolate_borrows_time_for_squeezed_words() {
+        let anchors = vec![Some((0, 500)), None, Some((500, 1000))];
+        let times = interpolate_times(anchors, &[3, 3, 3], (0, 1000), None);
+        assert_eq!(times[1].1 - times[1].0, MIN_WORD_MS);
+        assert_eq!(times[0].1, times[1].0);
+        assert_eq!(times[1].1, times[2].0);
+    }
+
+    #[test]
+    fn interpolate_leading_words_use_average_pace() {
+        let anchors = vec![None, None, Some((2000, 2200))];
+        let times = interpolate_times(anchors, &[1, 1, 1], (2000, 2200), None);
+        assert_eq!(times[0].0, 2000 - 2 * AVG_WORD_MS);
+        assert_eq!(times[1].1, 2000);
+    }
+
+    #[test]
+    fn segments_break_at_sentences_and_pauses() {
+        let words = tokenize_script("One two. Three four five");
+        let times = [(0, 100), (100, 200), (200, 300), (300, 400), (1500, 1600)];
+        let segments = build_segments(&words, &times, false, None);
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["One two.", "Three four", "five"]);
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (200, 400));
+    }
+}
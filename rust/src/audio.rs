@@ -39,7 +39,7 @@ pub async fn extract_audio(id: &str, p: ExtractAudioParams, mut emit: impl FnMut
     } else {
         emit(RpcEvent::Log {
             id: id.into(),
-            message: format!("Re-encoding audio to {}", target_codec).into()
+            message: format!("Re-encoding audio to {}", target_codec)
         });
         &target_codec
     };
@@ -63,3 +63,39 @@ pub async fn extract_audio(id: &str, p: ExtractAudioParams, mut emit: impl FnMut
     }
     Ok(ExtractAudioResult { audio: out })
 }
+
+/// One speaker's microphone in a multitrack recording
+#[derive(Debug, Clone, Copy)]
+pub enum SpeakerSource {
+    Stream(u32),  // n-th audio stream
+    Channel(u32), // n-th channel of the first audio stream
+}
+
+/// Write a single speaker's audio as 16 kHz mono WAV, ready for any transcription backend
+pub async fn extract_speaker_audio(input: &str, source: SpeakerSource, out: &std::path::Path) -> anyhow::Result<()> {
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let mut cmd = TokioCommand::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error").arg("-i").arg(input);
+
+    match source {
+        SpeakerSource::Stream(n) => {
+            cmd.arg("-map").arg(format!("0:a:{}", n));
+        }
+        SpeakerSource::Channel(n) => {
+            let pan = crate::filtergraph::Filter::new("pan").arg(format!("mono|c0=c{}", n));
+            cmd.arg("-map").arg("0:a:0").arg("-af").arg(pan.to_string());
+        }
+    }
+
+    let output = cmd
+        .arg("-ar").arg("16000")
+        .arg("-ac").arg("1")
+        .arg("-c:a").arg("pcm_s16le")
+        .arg(out)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffmpeg failed to extract {:?}: {}", source, String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(())
+}
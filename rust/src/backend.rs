@@ -0,0 +1,267 @@
+use crate::rpc::RpcEvent;
+use crate::types::{BackendCapabilities, TranscribeSegmentsParams, TranscriptionTask, WhisperResponse};
+use futures_util::future::{BoxFuture, FutureExt};
+use std::sync::LazyLock;
+
+/// Event sink handed to backends (boxed futures need a concrete, Send type)
+pub type Emit<'a> = &'a mut (dyn FnMut(RpcEvent) + Send);
+
+/// A speech-to-text engine that can turn an audio file into a WhisperResponse
+pub trait TranscriptionBackend: Send + Sync {
+    /// Registry name, used by `backend` / `backendPreference` params
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> BackendCapabilities;
+
+    /// Cheap check that the engine can run right now (binary, model, API key...)
+    fn is_available<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, bool>;
+
+    /// Model that would actually run for this request, after local model fallbacks.
+    /// Part of the transcription cache key.
+    fn resolved_model<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, String>;
+
+    fn transcribe<'a>(&'a self, id: &'a str, p: &'a TranscribeSegmentsParams, emit: Emit<'a>)
+        -> BoxFuture<'a, anyhow::Result<WhisperResponse>>;
+}
+
+// Registry order is the default preference order: warm/in-process engines first, the API last
+static REGISTRY: LazyLock<Vec<Box<dyn TranscriptionBackend>>> = LazyLock::new(|| {
+    vec![
+        #[cfg(feature = "native-whisper")]
+        Box::new(NativeWhisperBackend),
+        Box::new(WhisperServerBackend),
+        Box::new(WhisperCppBackend),
+        Box::new(FfmpegWhisperBackend),
+        Box::new(OpenAiBackend),
+    ]
+});
+
+pub fn backends() -> impl Iterator<Item = &'static dyn TranscriptionBackend> {
+    REGISTRY.iter().map(|b| b.as_ref())
+}
+
+pub fn find_backend(name: &str) -> Option<&'static dyn TranscriptionBackend> {
+    backends().find(|b| b.name() == name)
+}
+
+/// Backends to try for this request, in order.
+/// An explicit `backend` wins, then `backendPreference`, then the registry order.
+/// Choosing the "whisper-1" model keeps its old meaning: OpenAI API only.
+/// With `diarize`, backends that can label speakers move to the front.
+pub fn select_backends(p: &TranscribeSegmentsParams) -> anyhow::Result<Vec<&'static dyn TranscriptionBackend>> {
+    let lookup = |name: &str| find_backend(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown transcription backend: {}", name));
+
+    let candidates: Vec<&'static dyn TranscriptionBackend> = if let Some(name) = &p.backend {
+        vec![lookup(name)?]
+    } else if let Some(preference) = &p.backend_preference {
+        preference.iter().map(|n| lookup(n)).collect::<anyhow::Result<_>>()?
+    } else if p.model.as_deref() == Some("whisper-1") {
+        vec![lookup("openai")?]
+    } else {
+        backends().collect()
+    };
+
+    let mut eligible: Vec<_> = candidates.into_iter()
+        .filter(|b| p.task != TranscriptionTask::Translate || b.capabilities().translation)
+        .collect();
+    // Speaker labels are nice to have: try engines that produce them first, but keep the rest as fallbacks
+    if p.diarize {
+        eligible.sort_by_key(|b| !b.capabilities().diarization);
+    }
+
+    if eligible.is_empty() {
+        return Err(anyhow::anyhow!("No selected transcription backend supports task \"{}\"", p.task.as_str()));
+    }
+    Ok(eligible)
+}
+
+/// Run one backend, splitting long recordings into parallel chunks when the backend allows it
+pub async fn run_backend(
+    id: &str,
+    backend: &dyn TranscriptionBackend,
+    p: &TranscribeSegmentsParams,
+    temp_dir: Option<&std::path::PathBuf>,
+    emit: Emit<'_>,
+) -> anyhow::Result<WhisperResponse> {
+    let caps = backend.capabilities();
+    if p.prompt.is_some() && !caps.prompt {
+        emit(RpcEvent::Log { id: id.into(), message: format!("{} does not support prompts; prompt and vocabulary are ignored", backend.name()) });
+    }
+    if p.diarize && !caps.diarization {
+        emit(RpcEvent::Log { id: id.into(), message: format!("{} cannot detect speakers; captions will have no speaker labels", backend.name()) });
+    }
+
+    // Chunks are mono and number speakers independently, so speaker detection needs one pass
+    if caps.parallel_chunks && !p.diarize {
+        let duration = crate::video::probe(id, &p.audio, &mut *emit).await.ok().and_then(|r| r.duration);
+        if let Some(duration) = duration.filter(|d| *d > crate::chunking::CHUNKING_MIN_DURATION) {
+            let work_dir = match temp_dir {
+                Some(dir) => dir.join("chunks"),
+                None => std::env::temp_dir().join(format!("capslap_chunks_{}", id)),
+            };
+            let result = crate::chunking::transcribe_chunked(id, &p.audio, duration, &work_dir, &mut *emit, |chunk_path| {
+                let chunk_params = TranscribeSegmentsParams { audio: chunk_path, ..p.clone() };
+                async move {
+                    let mut quiet = |_: RpcEvent| {};
+                    backend.transcribe(id, &chunk_params, &mut quiet).await
+                }
+            }).await;
+            let _ = tokio::fs::remove_dir_all(&work_dir).await;
+
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e) => emit(RpcEvent::Log {
+                    id: id.into(),
+                    message: format!("Chunked transcription failed: {}, transcribing in one pass", e)
+                }),
+            }
+        }
+    }
+
+    backend.transcribe(id, p, emit).await
+}
+
+// ---- Built-in backends ----
+
+// The whisper.cpp engines default to "tiny" and fall back to smaller installed models
+async fn local_model(p: &TranscribeSegmentsParams) -> String {
+    let requested = p.model.clone().unwrap_or_else(|| "tiny".to_string());
+    match crate::whisper::ensure_whisper_model(&requested).await {
+        Ok((_, actual)) => actual,
+        Err(_) => requested,
+    }
+}
+
+#[cfg(feature = "native-whisper")]
+struct NativeWhisperBackend;
+
+#[cfg(feature = "native-whisper")]
+impl TranscriptionBackend for NativeWhisperBackend {
+    fn name(&self) -> &'static str { "native" }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // One model copy per chunk would be too much memory for in-process inference
+        BackendCapabilities { word_timestamps: true, translation: true, language_detect: true, prompt: true, parallel_chunks: false, diarization: false }
+    }
+
+    fn is_available<'a>(&'a self, _p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, bool> {
+        async { true }.boxed()
+    }
+
+    fn resolved_model<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, String> {
+        local_model(p).boxed()
+    }
+
+    fn transcribe<'a>(&'a self, id: &'a str, p: &'a TranscribeSegmentsParams, emit: Emit<'a>)
+        -> BoxFuture<'a, anyhow::Result<WhisperResponse>> {
+        async move {
+            let transcript = crate::whisper_native::transcribe_native(
+                id, &p.audio, p.model.clone(), p.language.clone(), p.task, p.prompt.clone(), emit
+            ).await?;
+            Ok(transcript.to_whisper_response())
+        }.boxed()
+    }
+}
+
+struct WhisperServerBackend;
+
+impl TranscriptionBackend for WhisperServerBackend {
+    fn name(&self) -> &'static str { "whisper-server" }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // The server decodes one request at a time, so chunks would just queue up
+        BackendCapabilities { word_timestamps: true, translation: true, language_detect: true, prompt: true, parallel_chunks: false, diarization: false }
+    }
+
+    fn is_available<'a>(&'a self, _p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, bool> {
+        async { crate::whisper_server::find_whisper_server_binary().is_ok() }.boxed()
+    }
+
+    fn resolved_model<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, String> {
+        local_model(p).boxed()
+    }
+
+    fn transcribe<'a>(&'a self, id: &'a str, p: &'a TranscribeSegmentsParams, emit: Emit<'a>)
+        -> BoxFuture<'a, anyhow::Result<WhisperResponse>> {
+        crate::whisper_server::transcribe_with_whisper_server(
+            id, &p.audio, p.model.clone(), p.language.clone(), p.task, p.prompt.clone(), emit
+        ).boxed()
+    }
+}
+
+struct WhisperCppBackend;
+
+impl TranscriptionBackend for WhisperCppBackend {
+    fn name(&self) -> &'static str { "whisper-cpp" }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities { word_timestamps: true, translation: true, language_detect: true, prompt: true, parallel_chunks: true, diarization: true }
+    }
+
+    fn is_available<'a>(&'a self, _p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, bool> {
+        crate::video::is_whisper_cpp_available().boxed()
+    }
+
+    fn resolved_model<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, String> {
+        local_model(p).boxed()
+    }
+
+    fn transcribe<'a>(&'a self, id: &'a str, p: &'a TranscribeSegmentsParams, emit: Emit<'a>)
+        -> BoxFuture<'a, anyhow::Result<WhisperResponse>> {
+        crate::whisper::transcribe_with_whisper_cpp(
+            id, &p.audio, p.model.clone(), p.language.clone(), p.task, p.prompt.clone(), p.diarize, emit
+        ).boxed()
+    }
+}
+
+struct FfmpegWhisperBackend;
+
+impl TranscriptionBackend for FfmpegWhisperBackend {
+    fn name(&self) -> &'static str { "ffmpeg-whisper" }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        // The FFmpeg whisper filter has no translate or prompt option and prints segments only
+        BackendCapabilities { word_timestamps: false, translation: false, language_detect: true, prompt: false, parallel_chunks: true, diarization: false }
+    }
+
+    fn is_available<'a>(&'a self, _p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, bool> {
+        crate::video::is_ffmpeg_whisper_available().boxed()
+    }
+
+    fn resolved_model<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, String> {
+        async move { p.model.clone().unwrap_or_else(|| "medium".to_string()) }.boxed()
+    }
+
+    fn transcribe<'a>(&'a self, id: &'a str, p: &'a TranscribeSegmentsParams, emit: Emit<'a>)
+        -> BoxFuture<'a, anyhow::Result<WhisperResponse>> {
+        crate::whisper::transcribe_with_ffmpeg_whisper(id, &p.audio, p.model.clone(), p.language.clone(), emit).boxed()
+    }
+}
+
+struct OpenAiBackend;
+
+impl TranscriptionBackend for OpenAiBackend {
+    fn name(&self) -> &'static str { "openai" }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities { word_timestamps: true, translation: true, language_detect: true, prompt: true, parallel_chunks: false, diarization: false }
+    }
+
+    fn is_available<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, bool> {
+        // Self-hosted OpenAI-compatible servers often need no key
+        async move {
+            p.api_key.as_deref().is_some_and(|k| !k.is_empty())
+                || p.api_endpoint.as_ref().is_some_and(|e| e.base_url.is_some())
+        }.boxed()
+    }
+
+    fn resolved_model<'a>(&'a self, p: &'a TranscribeSegmentsParams) -> BoxFuture<'a, String> {
+        async move { crate::whisper::api_model(p) }.boxed()
+    }
+
+    fn transcribe<'a>(&'a self, id: &'a str, p: &'a TranscribeSegmentsParams, emit: Emit<'a>)
+        -> BoxFuture<'a, anyhow::Result<WhisperResponse>> {
+        crate::whisper::transcribe_with_openai(id, p, emit).boxed()
+    }
+}
@@ -28,7 +28,10 @@ async fn main() -> anyhow::Result<()> {
     }
 
     // Wait for all tasks to complete (though this won't be reached in normal operation)
-    while let Some(_) = tasks.join_next().await {}
+    while tasks.join_next().await.is_some() {}
+
+    // stdin closed - don't leave warm whisper-server processes behind
+    core::whisper_server::shutdown_all_servers().await;
     Ok(())
 }
 
@@ -64,18 +67,92 @@ async fn handle_request(r: RpcRequest) {
         }
         "downloadModel" => {
             let p: core::types::DownloadModelParams = serde_json::from_value(r.params).unwrap();
-            match core::whisper::download_model_rpc(&id, p, &mut emit).await {
+            match core::models::download_model_rpc(&id, p, &mut emit).await {
                 Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
                 Err(e) => write_err(e.to_string()),
             }
         }
         "checkModelExists" => {
             let model_name: String = serde_json::from_value(r.params).unwrap();
-            match core::whisper::check_model_exists(&model_name) {
+            match core::models::check_model_exists(&model_name) {
                 Ok(exists) => write_ok(serde_json::to_value(exists).unwrap()),
                 Err(e) => write_err(e.to_string()),
             }
         }
+        "listModels" => {
+            match core::models::list_models().await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "importModel" => {
+            let p: core::types::ImportModelParams = serde_json::from_value(r.params).unwrap();
+            match core::models::import_model(&id, p, &mut emit).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "registerModel" => {
+            let p: core::types::RegisterModelParams = serde_json::from_value(r.params).unwrap();
+            match core::models::register_model(p).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "deleteModel" => {
+            let p: core::types::DeleteModelParams = serde_json::from_value(r.params).unwrap();
+            match core::models::delete_model(p).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "cacheStats" => {
+            match core::cache::cache_stats().await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "clearCache" => {
+            match core::cache::clear_cache().await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "setCacheBudget" => {
+            let p: core::types::SetCacheBudgetParams = serde_json::from_value(r.params).unwrap();
+            match core::cache::set_cache_budget(p).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "getVocabulary" => {
+            let p: core::types::GetVocabularyParams = serde_json::from_value(r.params).unwrap();
+            match core::vocabulary::get_vocabulary(p).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "setVocabulary" => {
+            let p: core::types::SetVocabularyParams = serde_json::from_value(r.params).unwrap();
+            match core::vocabulary::set_vocabulary(p).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "alignTranscript" => {
+            let p: core::types::AlignTranscriptParams = serde_json::from_value(r.params).unwrap();
+            match core::alignment::align_transcript(&id, p, &mut emit).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
+        "applyTextEdits" => {
+            let p: core::types::ApplyTextEditsParams = serde_json::from_value(r.params).unwrap();
+            match core::textedit::apply_text_edits(&id, p, &mut emit).await {
+                Ok(v) => write_ok(serde_json::to_value(v).unwrap()),
+                Err(e) => write_err(e.to_string()),
+            }
+        }
         _ => write_err("Unknown method".into()),
     }
 }
@@ -0,0 +1,184 @@
+use crate::types::{CacheStats, ClearCacheResult, SetCacheBudgetParams, TranscribeSegmentsParams, WhisperCacheEntry, WhisperCacheIndex, WhisperResponse};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+const DEFAULT_MAX_BYTES: u64 = 200 * 1024 * 1024;
+const INDEX_FILE: &str = "index.json";
+
+// Held for every read-modify-write of the index, so concurrent transcriptions
+// can't drop each other's entries or orphan response files
+static INDEX_LOCK: Mutex<()> = Mutex::const_new(());
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Cache directory for transcriptions and the render index. It lives in the app data dir,
+/// not the OS temp dir, so cached transcriptions survive reboots and temp cleaners.
+pub fn get_cache_dir() -> anyhow::Result<PathBuf> {
+    let cache_dir = crate::whisper::get_app_data_dir()?.join("cache");
+    std::fs::create_dir_all(&cache_dir)?;
+    Ok(cache_dir)
+}
+
+/// What a cached transcription depends on: the audio content, the request, and the
+/// backend and model that actually produce it (a "large" request served by a fallback
+/// "base" model must not be reused once "large" is installed)
+pub struct CacheKey {
+    audio_hash: String,
+    params_hash: String,
+    backend: String,
+    model: String,
+}
+
+impl CacheKey {
+    pub fn new(audio_hash: &str, params: &TranscribeSegmentsParams, backend: &str, model: &str) -> Self {
+        // video_file is left out as it doesn't affect transcription
+        let params_for_hash = serde_json::json!({
+            "backend": backend,
+            "model": model,
+            "language": params.language,
+            "split_by_words": params.split_by_words,
+            "prompt": params.prompt,
+            "task": params.task,
+            "diarize": params.diarize,
+            "api_base_url": params.api_endpoint.as_ref().and_then(|e| e.base_url.as_ref()),
+        });
+        Self {
+            audio_hash: audio_hash.to_string(),
+            params_hash: blake3::hash(params_for_hash.to_string().as_bytes()).to_hex().to_string(),
+            backend: backend.to_string(),
+            model: model.to_string(),
+        }
+    }
+
+    fn matches(&self, entry: &WhisperCacheEntry) -> bool {
+        entry.audio_hash == self.audio_hash && entry.params_hash == self.params_hash
+    }
+}
+
+/// Sibling temp path for an atomic write-then-rename of `path`. The name is unique per
+/// process and call, so concurrent writers never rename each other's half-written files.
+pub fn unique_tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.{}.tmp", std::process::id(), TMP_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    path.with_file_name(name)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+async fn load_cache_index() -> anyhow::Result<WhisperCacheIndex> {
+    let index_path = get_cache_dir()?.join(INDEX_FILE);
+    match fs::read_to_string(&index_path).await {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(_) => Ok(WhisperCacheIndex::default()),
+    }
+}
+
+async fn save_cache_index(index: &WhisperCacheIndex) -> anyhow::Result<()> {
+    let index_path = get_cache_dir()?.join(INDEX_FILE);
+    // Write then rename so a crash never leaves a truncated index; the tmp name is unique
+    // so another process sharing the cache dir never renames a half-written file
+    let tmp = unique_tmp_path(&index_path);
+    fs::write(&tmp, serde_json::to_string_pretty(index)?).await?;
+    fs::rename(&tmp, &index_path).await?;
+    Ok(())
+}
+
+/// Drop least recently used entries until the cache fits its budget
+async fn evict(index: &mut WhisperCacheIndex) {
+    let budget = index.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+    index.entries.sort_by_key(|e| e.timestamp);
+    let mut total: u64 = index.entries.iter().map(|e| e.size).sum();
+    while total > budget && !index.entries.is_empty() {
+        let entry = index.entries.remove(0);
+        let _ = fs::remove_file(&entry.response_path).await;
+        total -= entry.size;
+    }
+}
+
+pub async fn get_cached_whisper_response(key: &CacheKey) -> anyhow::Result<Option<WhisperResponse>> {
+    let _guard = INDEX_LOCK.lock().await;
+    let mut index = load_cache_index().await?;
+    let Some(position) = index.entries.iter().position(|e| key.matches(e)) else {
+        return Ok(None);
+    };
+
+    let response = match fs::read_to_string(&index.entries[position].response_path).await {
+        Ok(content) => serde_json::from_str::<WhisperResponse>(&content).ok(),
+        Err(_) => None,
+    };
+    match &response {
+        Some(_) => index.entries[position].timestamp = now_secs(),
+        // Deleted or unreadable: forget it
+        None => {
+            let entry = index.entries.remove(position);
+            let _ = fs::remove_file(&entry.response_path).await;
+        }
+    }
+    save_cache_index(&index).await?;
+    Ok(response)
+}
+
+pub async fn save_cached_whisper_response(key: &CacheKey, response: &WhisperResponse) -> anyhow::Result<()> {
+    let cache_dir = get_cache_dir()?;
+    let cache_filename = format!("{}_{}.json", &key.audio_hash[..8], &key.params_hash[..8]);
+    let cached_json_path = cache_dir.join(cache_filename);
+    let json_content = serde_json::to_string_pretty(response)?;
+    let _guard = INDEX_LOCK.lock().await;
+    fs::write(&cached_json_path, &json_content).await?;
+
+    let mut index = load_cache_index().await?;
+    index.entries.retain(|e| !key.matches(e));
+    index.entries.push(WhisperCacheEntry {
+        audio_hash: key.audio_hash.clone(),
+        params_hash: key.params_hash.clone(),
+        response_path: cached_json_path.to_string_lossy().to_string(),
+        timestamp: now_secs(),
+        backend: key.backend.clone(),
+        model: key.model.clone(),
+        size: json_content.len() as u64,
+    });
+    evict(&mut index).await;
+    save_cache_index(&index).await
+}
+
+pub async fn cache_stats() -> anyhow::Result<CacheStats> {
+    let index = {
+        let _guard = INDEX_LOCK.lock().await;
+        load_cache_index().await?
+    };
+    Ok(CacheStats {
+        dir: get_cache_dir()?.to_string_lossy().to_string(),
+        entries: index.entries.len(),
+        bytes: index.entries.iter().map(|e| e.size).sum(),
+        max_bytes: index.max_bytes.unwrap_or(DEFAULT_MAX_BYTES),
+    })
+}
+
+/// Delete every cached transcription; the size budget is kept
+pub async fn clear_cache() -> anyhow::Result<ClearCacheResult> {
+    let _guard = INDEX_LOCK.lock().await;
+    let mut index = load_cache_index().await?;
+    let entries = std::mem::take(&mut index.entries);
+    let freed = entries.iter().map(|e| e.size).sum();
+    for entry in &entries {
+        let _ = fs::remove_file(&entry.response_path).await;
+    }
+    save_cache_index(&index).await?;
+    Ok(ClearCacheResult { removed: entries.len(), freed })
+}
+
+pub async fn set_cache_budget(p: SetCacheBudgetParams) -> anyhow::Result<CacheStats> {
+    {
+        let _guard = INDEX_LOCK.lock().await;
+        let mut index = load_cache_index().await?;
+        index.max_bytes = p.max_bytes;
+        evict(&mut index).await;
+        save_cache_index(&index).await?;
+    }
+    cache_stats().await
+}
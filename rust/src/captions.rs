@@ -1,15 +1,15 @@
 use anyhow::{anyhow, Result};
 use crate::rpc::RpcEvent;
-use crate::types::{CaptionSegment, WordSpan, GenerateCaptionsParams, GenerateCaptionsResult, CaptionedVideoResult, ExtractAudioParams, TranscribeSegmentsParams};
+use crate::types::{CaptionSegment, WordSpan, GenerateCaptionsParams, GenerateCaptionsResult, CaptionedVideoResult, ExtractAudioParams, TranscribeSegmentsParams, TranscribeSegmentsResult, TranscriptionTask, RenderCacheEntry, RenderCacheIndex, TimeRange};
 use crate::video::probe;
 use crate::{audio, whisper};
-use std::{fs, path::PathBuf, process::Command};
+use std::{fs, path::Path, process::Command};
 use std::collections::{HashMap, HashSet, VecDeque};
 
 pub async fn generate_captions(
     id: &str,
     params: GenerateCaptionsParams,
-    emit: impl FnMut(RpcEvent)
+    emit: impl FnMut(RpcEvent) + Send
 ) -> Result<GenerateCaptionsResult> {
     generate_captions_single_pass(id, params, emit).await
 }
@@ -17,7 +17,7 @@ pub async fn generate_captions(
 pub async fn generate_captions_single_pass(
     id: &str,
     params: GenerateCaptionsParams,
-    mut emit: impl FnMut(RpcEvent)
+    mut emit: impl FnMut(RpcEvent) + Send
 ) -> Result<GenerateCaptionsResult> {
 
     let temp_dir = std::env::temp_dir().join(format!("capslap_captions_{}", id));
@@ -27,59 +27,206 @@ pub async fn generate_captions_single_pass(
 
     let probe_result = probe(id, &params.input_video, &mut emit).await?;
 
-    let audio_filename = format!("audio_{}.mp3", id);
-    let temp_audio_path = temp_dir.join(&audio_filename);
-    let audio_params = ExtractAudioParams {
-        input: params.input_video.clone(),
-        codec: Some("mp3".to_string()),
-        out: Some(temp_audio_path.to_string_lossy().to_string()),
+    // A chosen embedded track is extracted and imported like a subtitle file
+    let subtitle_source = match params.subtitle_track {
+        Some(index) => {
+            let track = probe_result.subtitle_tracks.iter()
+                .find(|t| t.index == index)
+                .ok_or_else(|| anyhow!("Input has no subtitle stream with index {}", index))?;
+            emit(RpcEvent::Log {
+                id: id.into(),
+                message: format!("Extracting embedded {} subtitle track {} ({})",
+                    track.codec, track.index, track.language.as_deref().unwrap_or("unknown language"))
+            });
+            let path = crate::subtitles::extract_embedded_track(&params.input_video, track, &temp_dir).await?;
+            Some((path, track.language.as_deref().and_then(crate::subtitles::track_language)))
+        }
+        None => params.subtitle_file.clone().map(|path| (path, None)),
+    };
+
+    // Imported subtitles are burned in as they are: no audio extraction, no transcription
+    let (audio_file, transcription, translation) = if let Some((subtitle_file, language)) = subtitle_source {
+        let mut segments = crate::subtitles::import_subtitles(&subtitle_file).await?;
+        for seg in &mut segments {
+            seg.language = language.clone();
+        }
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: format!("Imported {} subtitle cues from {}, skipping transcription", segments.len(), subtitle_file)
+        });
+        if params.bilingual {
+            emit(RpcEvent::Log { id: id.into(), message: "Bilingual captions need a transcription pass; ignored for imported subtitles".into() });
+        }
+
+        let transcription = TranscribeSegmentsResult {
+            full_text: segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+            segments,
+            duration: probe_result.duration,
+            json_file: subtitle_file,
+            language,
+            low_confidence_words: Vec::new(),
+        };
+        (String::new(), transcription, None)
+    } else {
+        let audio_filename = format!("audio_{}.mp3", id);
+        let temp_audio_path = temp_dir.join(&audio_filename);
+        let audio_params = ExtractAudioParams {
+            input: params.input_video.clone(),
+            codec: Some("mp3".to_string()),
+            out: Some(temp_audio_path.to_string_lossy().to_string()),
+        };
+        let audio_result = audio::extract_audio(id, audio_params, &mut emit).await?;
+
+        let transcribe_params = TranscribeSegmentsParams {
+            audio: audio_result.audio.clone(),
+            model: params.model,
+            language: params.language,
+            split_by_words: params.split_by_words,
+            api_key: params.api_key.clone(),
+            prompt: params.prompt,
+            video_file: Some(params.input_video.clone()),
+            // Bilingual output always keeps the original speech as the main captions
+            task: if params.bilingual { TranscriptionTask::Transcribe } else { params.task },
+            project: params.project,
+            backend: params.backend,
+            backend_preference: params.backend_preference,
+            api_endpoint: params.api_endpoint,
+            diarize: params.diarize,
+        };
+        let translate_params = TranscribeSegmentsParams {
+            split_by_words: false, // translation lines are shown per segment
+            task: TranscriptionTask::Translate,
+            ..transcribe_params.clone()
+        };
+        let transcription = match params.multitrack {
+            Some(mode) => crate::multitrack::transcribe_multitrack(
+                id, &params.input_video, &probe_result, mode, &transcribe_params, &temp_dir, &mut emit
+            ).await?,
+            None => whisper::transcribe_segments_with_temp(id, transcribe_params, Some(&temp_dir), &mut emit).await?,
+        };
+
+        let translation = if params.bilingual {
+            emit(RpcEvent::Log { id: id.into(), message: "Running English translation pass for bilingual captions".into() });
+            Some(whisper::transcribe_segments_with_temp(id, translate_params, Some(&temp_dir), &mut emit).await?)
+        } else {
+            None
+        };
+
+        (audio_result.audio, transcription, translation)
     };
-    let audio_result = audio::extract_audio(id, audio_params, &mut emit).await?;
 
+    let (mut transcription, mut translation) = (transcription, translation);
+    if let Some(cleanup) = &params.filler_cleanup {
+        let language = transcription.language.clone();
+        let cleaned = crate::fillers::clean_segments(&mut transcription.segments, cleanup, language.as_deref());
+        if let Some(translation) = translation.as_mut() {
+            crate::fillers::clean_segments(&mut translation.segments, cleanup, Some("en"));
+        }
+        emit(RpcEvent::Log { id: id.into(), message: format!("Cleaned up {} filler word(s) and repeats ({:?})", cleaned, cleanup.mode) });
+    }
 
-    let transcribe_params = TranscribeSegmentsParams {
-        audio: audio_result.audio.clone(),
-        model: params.model,
-        language: params.language,
-        split_by_words: params.split_by_words,
-        api_key: params.api_key.clone(),
-        prompt: params.prompt,
-        video_file: Some(params.input_video.clone()),
+    // Mask captions before anything is rendered; the same word times drive the bleeps
+    let bleeps = match &params.redaction {
+        Some(redaction) => {
+            let redactor = crate::redaction::Redactor::new(redaction)?;
+            let ranges = redactor.redact_transcription(&mut transcription);
+            if let Some(translation) = translation.as_mut() {
+                redactor.redact_transcription(translation);
+            }
+            emit(RpcEvent::Log { id: id.into(), message: format!("Redacted {} passage(s)", ranges.len()) });
+            crate::redaction::audio_filter(&ranges, redaction.audio)
+        }
+        None => None,
     };
-    let transcription = whisper::transcribe_segments_with_temp(id, transcribe_params, Some(&temp_dir), &mut emit).await?;
+    let mut source_filters = SourceFilters { cuts: Vec::new(), audio: bleeps };
+
+    // Jump cuts come last: everything above works on source times
+    let mut cuts = Vec::new();
+    if let Some(jump_cuts) = &params.jump_cuts {
+        let silences = if jump_cuts.confirm_silence {
+            // Imported subtitles have no extracted audio; silencedetect reads the video's audio directly
+            let audio_source = if audio_file.is_empty() { &params.input_video } else { &audio_file };
+            Some(crate::chunking::detect_silences(audio_source).await?)
+        } else {
+            None
+        };
+        let duration_ms = probe_result.duration
+            .map(|d| (d * 1000.0) as u64)
+            .ok_or_else(|| anyhow!("Could not determine the duration of {}, which jump cuts need", params.input_video))?;
+        cuts = crate::jumpcut::plan_cuts(&transcription.segments, duration_ms, jump_cuts, silences.as_deref());
+
+        if !cuts.is_empty() {
+            let removed_ms: u64 = cuts.iter().map(|c| c.end_ms - c.start_ms).sum();
+            emit(RpcEvent::Log {
+                id: id.into(),
+                message: format!("Cutting {} pause(s), {:.1}s in total", cuts.len(), removed_ms as f64 / 1000.0)
+            });
+            crate::jumpcut::remap_transcription(&mut transcription, &cuts);
+            if let Some(translation) = translation.as_mut() {
+                crate::jumpcut::remap_transcription(translation, &cuts);
+            }
+            source_filters.cuts = cuts.clone();
+        }
+    }
+
+    let font_name = font_for_language(params.font_name.as_deref(), transcription.language.as_deref());
+    if font_name != params.font_name {
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: format!("Detected language {}, using font {}",
+                transcription.language.as_deref().unwrap_or("unknown"), font_name.as_deref().unwrap_or("default"))
+        });
+    }
 
     let captioned_videos = optimized_multi_format_encode(
         id,
         &params.input_video,
         &transcription.segments,
+        translation.as_ref().map(|t| t.segments.as_slice()),
         &params.export_formats,
         &probe_result,
         &temp_dir,
-        params.font_name,
+        font_name,
         params.text_color,
         params.highlight_word_color,
         params.outline_color,
         params.glow_effect,
         params.karaoke,
         params.position,
+        params.translation_color,
+        params.speaker_colors,
+        params.speaker_positions,
+        params.mark_low_confidence,
+        source_filters,
         &mut emit
     ).await?;
 
     Ok(GenerateCaptionsResult {
         probe_result,
-        audio_file: audio_result.audio,
+        audio_file,
         transcription,
+        translation,
         captioned_videos,
+        cuts,
     })
 }
 
+/// Edits made to the source before it is reframed and captioned
+#[derive(Debug, Clone, Default)]
+struct SourceFilters {
+    cuts: Vec<TimeRange>,   // jump cuts, on source times
+    audio: Option<String>,  // audio filter chain on source times, applied before the cuts (bleeps)
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn optimized_multi_format_encode(
     id: &str,
     input_video: &str,
     segments: &[CaptionSegment],
+    translation_segments: Option<&[CaptionSegment]>,
     export_formats: &[String],
     probe_result: &crate::video::ProbeResult,
-    temp_dir: &PathBuf,
+    temp_dir: &Path,
     font_name: Option<String>,
     text_color: Option<String>,
     highlight_word_color: Option<String>,
@@ -87,6 +234,11 @@ async fn optimized_multi_format_encode(
     glow_effect: bool,
     karaoke: bool,
     position: Option<String>,
+    translation_color: Option<String>,
+    speaker_colors: Option<Vec<String>>,
+    speaker_positions: Option<Vec<String>>,
+    mark_low_confidence: bool,
+    source_filters: SourceFilters,
     emit: &mut impl FnMut(RpcEvent)
 ) -> Result<Vec<CaptionedVideoResult>> {
     if export_formats.is_empty() {
@@ -98,8 +250,17 @@ async fn optimized_multi_format_encode(
         .to_string_lossy()
         .to_string();
 
+    // Encoder choice and input content are shared by every format's render cache key
+    let hardware_encoder = crate::video::get_best_hardware_encoder().await;
+    let video_hash = {
+        let input_video = input_video.to_string();
+        tokio::task::spawn_blocking(move || whisper::hash_file(&input_video)).await??
+    };
+    let render_index = load_render_cache_index().await;
+
     // Pre-generate shared ASS files for each format (avoiding redundant subtitle processing)
     let mut format_ass_files = Vec::new();
+    let mut captioned_videos: Vec<Option<CaptionedVideoResult>> = Vec::new();
     for format in export_formats {
         let target_ar = crate::video::parse_target_ar(format)?;
         let src_w = probe_result.width.unwrap_or(1920) as u32;
@@ -116,37 +277,60 @@ async fn optimized_multi_format_encode(
             glow_effect,
             position.as_deref()
         );
-        let ass_doc = build_ass_document(target_w, target_h, &style, segments, karaoke, glow_effect)?;
+        let translation_style = translation_ass_style(&style, target_h, translation_color.as_deref());
+        let translation = translation_segments.map(|t| (&translation_style, t));
+        let speaker_styles = speaker_ass_styles(&style, target_h, segments, speaker_colors.as_deref(), speaker_positions.as_deref());
+        let ass_doc = build_ass_document(target_w, target_h, &style, segments, translation, &speaker_styles, karaoke, glow_effect, mark_low_confidence)?;
 
         let safe_format = format.replace(':', "x");
+        // Review previews never overwrite the clean render
+        let review_suffix = if mark_low_confidence { "_review" } else { "" };
+        let captioned_path = format!("{}_{}{}.mp4", input_path, safe_format, review_suffix);
+
+        // Skip the encode entirely if this exact render already exists on disk
+        let settings = encoder_settings_key(probe_result, target_w, target_h, &source_filters);
+        let render_hash = compute_render_cache_key(&ass_doc, &video_hash, &settings);
+        if is_render_cached(&render_index, &captioned_path, &render_hash, hardware_encoder) {
+            emit(RpcEvent::Log {
+                id: id.into(),
+                message: format!("Reusing cached {} render: {}", format, captioned_path)
+            });
+            captioned_videos.push(Some(CaptionedVideoResult {
+                format: format.clone(),
+                raw_video: "".to_string(),
+                captioned_video: captioned_path,
+                width: target_w,
+                height: target_h,
+                cached: true,
+            }));
+            continue;
+        }
+
         let ass_filename = format!("captions_{}_{}.ass", id, safe_format);
         let ass_path = temp_dir.join(&ass_filename);
         fs::write(&ass_path, ass_doc)?;
 
-        format_ass_files.push((format.clone(), ass_path, target_w, target_h));
+        format_ass_files.push((captioned_videos.len(), format.clone(), ass_path, captioned_path, render_hash, target_w, target_h));
+        captioned_videos.push(None);
     }
 
     // Process formats with limited concurrency (2 at a time for optimal resource usage)
     let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(2));
     let mut tasks = Vec::new();
 
-    for (idx, (format, ass_path, target_w, target_h)) in format_ass_files.into_iter().enumerate() {
-        let format = format.clone();
+    for (slot, format, ass_path, captioned_path, render_hash, target_w, target_h) in format_ass_files {
         let input_video = input_video.to_string();
         let probe_result = probe_result.clone();
+        let source_filters = source_filters.clone();
         let semaphore = semaphore.clone();
-        let task_id = format!("{}_{}", id, idx);
-        let input_path = input_path.clone();
+        let task_id = format!("{}_{}", id, slot);
 
         let task = tokio::spawn(async move {
             // Acquire semaphore permit for bounded concurrency
             let _permit = semaphore.acquire().await.unwrap();
 
-            let safe_format = format.replace(':', "x");
-            let captioned_path = format!("{}_{}.mp4", input_path, safe_format);
-
             // Single-pass format conversion + caption burning with hardware acceleration
-            optimized_single_format_encode(
+            let used_encoder = optimized_single_format_encode(
                 &task_id,
                 &input_video,
                 &ass_path,
@@ -154,43 +338,153 @@ async fn optimized_multi_format_encode(
                 target_w,
                 target_h,
                 &probe_result,
+                hardware_encoder,
+                &source_filters,
             ).await?;
 
-            Ok::<CaptionedVideoResult, anyhow::Error>(CaptionedVideoResult {
+            Ok::<_, anyhow::Error>((CaptionedVideoResult {
                 format,
                 raw_video: "".to_string(),
                 captioned_video: captioned_path,
                 width: target_w,
                 height: target_h,
-            })
+                cached: false,
+            }, used_encoder))
         });
 
-        tasks.push(task);
+        tasks.push((slot, render_hash, task));
     }
 
     // Wait for all tasks to complete and collect results
-    let mut captioned_videos = Vec::new();
-    for (idx, task) in tasks.into_iter().enumerate() {
-        let result = task.await.map_err(|e| anyhow!("Concurrent task failed: {}", e))??;
-        captioned_videos.push(result);
+    let mut renders = Vec::new();
+    for (slot, render_hash, task) in tasks {
+        let (result, used_encoder) = task.await.map_err(|e| anyhow!("Concurrent task failed: {}", e))??;
+        renders.push((result.captioned_video.clone(), render_hash, used_encoder));
+        captioned_videos[slot] = Some(result);
+    }
+
+    if let Err(e) = record_renders(renders).await {
+        emit(RpcEvent::Log { id: id.into(), message: format!("Failed to save render cache: {}", e) });
+    }
+
+    Ok(captioned_videos.into_iter().flatten().collect())
+}
+
+// ---- Render cache ----
+// Bump when encode arguments change in a way that alters the output file
+const RENDER_CACHE_VERSION: u32 = 2;
+const RENDER_INDEX_FILE: &str = "render_index.json";
+// Held for the load-merge-save of the render index
+static RENDER_INDEX_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Everything besides the ASS document, input content and encoder that affects the encoded output.
+/// The encoder is recorded per entry instead, as a hardware encode may fall back to software.
+fn encoder_settings_key(
+    probe_result: &crate::video::ProbeResult,
+    target_w: u32,
+    target_h: u32,
+    source_filters: &SourceFilters,
+) -> serde_json::Value {
+    let (audio_codec, audio_args) = crate::video::determine_audio_codec(Some(probe_result));
+    let mut settings = serde_json::json!({
+        "version": RENDER_CACHE_VERSION,
+        "fps": probe_result.fps,
+        "width": target_w,
+        "height": target_h,
+        "audioCodec": audio_codec,
+        "audioArgs": audio_args,
+    });
+    // Only present when used, so plain renders keep their existing cache keys
+    if !source_filters.cuts.is_empty() {
+        settings["cuts"] = serde_json::to_value(&source_filters.cuts).unwrap_or_default();
+    }
+    if let Some(filter) = &source_filters.audio {
+        settings["audioFilter"] = filter.as_str().into();
+    }
+    settings
+}
+
+fn compute_render_cache_key(ass_doc: &str, video_hash: &str, settings: &serde_json::Value) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(ass_doc.as_bytes());
+    hasher.update(video_hash.as_bytes());
+    hasher.update(settings.to_string().as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+/// A render only counts if it was produced by the encoder this run would use
+fn is_render_cached(index: &RenderCacheIndex, output_path: &str, render_hash: &str, encoder: crate::video::HardwareEncoder) -> bool {
+    index.entries.iter().any(|e| {
+        e.output_path == output_path && e.render_hash == render_hash
+            && e.encoder == format!("{:?}", encoder)
+            && fs::metadata(output_path).map(|m| m.len() == e.size).unwrap_or(false)
+    })
+}
+
+fn record_render(index: &mut RenderCacheIndex, output_path: &str, render_hash: String, encoder: crate::video::HardwareEncoder) {
+    let Ok(meta) = fs::metadata(output_path) else { return; };
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    index.entries.retain(|e| e.output_path != output_path);
+    index.entries.push(RenderCacheEntry {
+        output_path: output_path.to_string(),
+        render_hash,
+        encoder: format!("{:?}", encoder),
+        size: meta.len(),
+        timestamp,
+    });
+}
+
+async fn load_render_cache_index() -> RenderCacheIndex {
+    let Ok(cache_dir) = crate::cache::get_cache_dir() else { return RenderCacheIndex::default(); };
+    match tokio::fs::read_to_string(cache_dir.join(RENDER_INDEX_FILE)).await {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => RenderCacheIndex::default(),
     }
+}
+
+/// Merge finished renders into the on-disk index. The index is re-read under the lock
+/// so concurrent generateCaptions runs don't drop each other's entries.
+async fn record_renders(renders: Vec<(String, String, crate::video::HardwareEncoder)>) -> Result<()> {
+    let _guard = RENDER_INDEX_LOCK.lock().await;
+    let mut index = load_render_cache_index().await;
+    for (output_path, render_hash, encoder) in renders {
+        record_render(&mut index, &output_path, render_hash, encoder);
+    }
+    save_render_cache_index(&index).await
+}
 
-    Ok(captioned_videos)
+async fn save_render_cache_index(index: &RenderCacheIndex) -> Result<()> {
+    let index_path = crate::cache::get_cache_dir()?.join(RENDER_INDEX_FILE);
+    // Drop entries whose outputs were deleted or moved
+    let entries = index.entries.iter()
+        .filter(|e| Path::new(&e.output_path).exists())
+        .cloned()
+        .collect();
+    let content = serde_json::to_string_pretty(&RenderCacheIndex { entries })?;
+    // Write then rename so readers never see a truncated index
+    let tmp = crate::cache::unique_tmp_path(&index_path);
+    tokio::fs::write(&tmp, content).await?;
+    tokio::fs::rename(&tmp, &index_path).await?;
+    Ok(())
 }
 
 /// Optimized single format encoding with hardware acceleration and modern FFmpeg flags
+#[allow(clippy::too_many_arguments)]
 async fn optimized_single_format_encode(
     id: &str,
     input_video: &str,
-    ass_path: &PathBuf,
+    ass_path: &Path,
     output_path: &str,
     target_w: u32,
     target_h: u32,
     probe_result: &crate::video::ProbeResult,
-) -> Result<()> {
-    // Determine the best available hardware encoder for H.264 first (for filter optimization)
-    let hardware_encoder = crate::video::get_best_hardware_encoder().await;
-
+    hardware_encoder: crate::video::HardwareEncoder,
+    source_filters: &SourceFilters,
+) -> Result<crate::video::HardwareEncoder> {
     // Try with hardware encoder first, then fallback to software if it fails
     let result = try_encode_with_encoder(
         id,
@@ -201,6 +495,7 @@ async fn optimized_single_format_encode(
         target_h,
         probe_result,
         hardware_encoder,
+        source_filters,
     ).await;
 
     // If hardware encoder failed, try software fallback
@@ -214,30 +509,56 @@ async fn optimized_single_format_encode(
             target_h,
             probe_result,
             crate::video::HardwareEncoder::Software,
-        ).await;
+            source_filters,
+        ).await.map(|_| crate::video::HardwareEncoder::Software);
     }
 
-    result
+    result.map(|_| hardware_encoder)
 }
 
 /// Helper function to try encoding with a specific encoder
+#[allow(clippy::too_many_arguments)]
 async fn try_encode_with_encoder(
     id: &str,
     input_video: &str,
-    ass_path: &PathBuf,
+    ass_path: &Path,
     output_path: &str,
     target_w: u32,
     target_h: u32,
     probe_result: &crate::video::ProbeResult,
     hardware_encoder: crate::video::HardwareEncoder,
+    source_filters: &SourceFilters,
 ) -> Result<()> {
     // Build optimized filter with format conversion AND subtitles in one pass
     // Use encoder-specific format optimization (NV12 for VideoToolbox/NVENC, yuv420p for software)
     let ass = ass_path.to_string_lossy().to_string();
     let vf = crate::video::build_fitpad_filter_with_format(target_w, target_h, Some(&ass), hardware_encoder);
+    let audio_filter = source_filters.audio.as_deref().filter(|_| probe_result.audio);
+
+    // Jump cuts need a filter graph: every kept range is trimmed on its own timestamps and
+    // the pieces are concatenated, then the result is reframed and captioned
+    let graph = (!source_filters.cuts.is_empty()).then(|| {
+        let duration_ms = (probe_result.duration.unwrap_or(0.0) * 1000.0) as u64;
+        let keeps = crate::jumpcut::keep_ranges(&source_filters.cuts, duration_ms);
+        let mut graph = Vec::new();
+        let audio_in = match audio_filter {
+            Some(filter) => {
+                graph.push(format!("[0:a:0]{}[asrc]", filter));
+                Some("[asrc]")
+            }
+            None => probe_result.audio.then_some("[0:a:0]"),
+        };
+        graph.push(crate::jumpcut::edit_graph(&keeps, Some("[0:v:0]"), audio_in, 0));
+        graph.push(format!("[vout]{}[vcap]", vf));
+        graph.join(";")
+    });
 
     // Determine optimal audio codec and settings
-    let (audio_codec, audio_args) = crate::video::determine_audio_codec(Some(probe_result));
+    let (mut audio_codec, mut audio_args) = crate::video::determine_audio_codec(Some(probe_result));
+    // Cut, bleeped or muted audio has to be re-encoded
+    if (audio_filter.is_some() || graph.is_some()) && audio_codec == "copy" {
+        (audio_codec, audio_args) = ("aac", Vec::new());
+    }
 
     // Calculate GOP size based on original video FPS for better seeking
     let gop_size = if let Some(fps) = probe_result.fps {
@@ -254,14 +575,29 @@ async fn try_encode_with_encoder(
 
     let status = Command::new(&ffmpeg_path)
         .args({
-            let mut args = vec![
-                "-y", "-i", input_video,
-                "-vf", &vf,
+            let mut args = vec!["-y", "-i", input_video];
+            match &graph {
+                Some(graph) => {
+                    args.extend_from_slice(&["-filter_complex", graph, "-map", "[vcap]"]);
+                    if probe_result.audio {
+                        args.extend_from_slice(&["-map", "[aout]"]);
+                    }
+                }
+                None => {
+                    args.extend_from_slice(&[
+                        "-vf", &vf,
+                        "-map", "0:v:0",              // Map first video stream
+                        "-map", "0:a?",               // Map audio if present (optional)
+                    ]);
+                    if let Some(filter) = audio_filter {
+                        args.extend_from_slice(&["-af", filter]);
+                    }
+                }
+            }
+            args.extend_from_slice(&[
                 "-fps_mode", "passthrough",       // Modern replacement for -vsync
                 "-threads", "0",                  // Use all available CPU cores
-                "-map", "0:v:0",                  // Map first video stream
-                "-map", "0:a?",                   // Map audio if present (optional)
-            ];
+            ]);
 
             // Add hardware-optimized encoding parameters
             match hardware_encoder {
@@ -299,7 +635,7 @@ async fn try_encode_with_encoder(
             }
 
             args.push("-c:a");
-            args.push(&audio_codec);
+            args.push(audio_codec);
 
             // Add audio-specific args
             args.extend(audio_args.iter().copied());
@@ -335,6 +671,7 @@ const STRETCH_X_PEAK: f32 = 1.03;  // 1.08–1.15 looks right
 const STRETCH_UP_MIN_MS: i64 = 0;
 const STRETCH_UP_MAX_MS: i64 = 150;
 const BIG_FONT_SIZE_MULTIPLIER: f32 = 1.1;
+const LOW_CONFIDENCE_BGR: &str = "3030FF"; // red, for review previews
 
 // ---- Constants for bounce animation (non-karaoke) ----
 const BOUNCE_START: f32 = 0.85;   // 95%
@@ -350,6 +687,7 @@ const HL_MIN_GAP_MS: u64 = 1200;    // min time between highlights
 const HL_MAX_RATIO: f32 = 0.35;     // cap ~35% of phrases highlighted
 const HL_RECENT_WINDOW_MS: u64 = 5000; // window for repetition penalty
 
+#[allow(clippy::too_many_arguments)]
 fn push_glow_and_stroke(
     lines: &mut String,
     start: &str, end: &str,
@@ -388,25 +726,47 @@ struct Phrase {
     end_ms: u64,
     tokens: Vec<String>,     // plain words for layout
     spans:  Vec<WordSpan>,   // timings per token (same length as tokens)
+    speaker: Option<u32>,
 }
 
 // Heuristics: new phrase if punctuation on previous token or gap > 350ms or length > 3 words
 fn coalesce_phrases(segments: &[CaptionSegment]) -> Vec<Phrase> {
     let mut all: Vec<WordSpan> = Vec::new();
+    // A hidden filler isn't shown; the word before it stays up until it's over, so the
+    // filler's time doesn't open a gap between karaoke windows
+    let absorb_hidden = |all: &mut Vec<WordSpan>, start_ms: u64, end_ms: u64, speaker: Option<u32>| {
+        // Only when it follows on directly (the same gap that starts a new phrase)
+        if let Some(prev) = all.last_mut().filter(|p| p.speaker == speaker && start_ms.saturating_sub(p.end_ms) <= 350) {
+            prev.end_ms = prev.end_ms.max(end_ms);
+        }
+    };
     for s in segments {
+        if s.hidden {
+            absorb_hidden(&mut all, s.start_ms, s.end_ms, s.speaker);
+            continue;
+        }
         for w in &s.words {
+            if w.hidden {
+                absorb_hidden(&mut all, w.start_ms, w.end_ms, w.speaker.or(s.speaker));
+                continue;
+            }
             let t = w.text.trim();
-            if !t.is_empty() { all.push(WordSpan { start_ms: w.start_ms, end_ms: w.end_ms, text: t.to_string() }); }
+            if !t.is_empty() { all.push(WordSpan { start_ms: w.start_ms, end_ms: w.end_ms, text: t.to_string(), speaker: w.speaker.or(s.speaker), confidence: w.confidence.or(s.confidence), hidden: false }); }
         }
         // Fallback: if a segment has text but no words, split evenly so nothing gets dropped
+        // (each piece inherits the segment's confidence)
         if s.words.is_empty() && !s.text.trim().is_empty() {
-            let toks: Vec<_> = s.text.split_whitespace().collect();
+            // No-break spaces are digit group separators ("225 000"), not word breaks
+            let toks: Vec<_> = s.text
+                .split(|c: char| c.is_whitespace() && !matches!(c, '\u{00A0}' | '\u{202F}'))
+                .filter(|t| !t.is_empty())
+                .collect();
             let total = (s.end_ms - s.start_ms).max(1);
             let per = total / (toks.len().max(1) as u64);
             let mut t = s.start_ms;
             for tok in toks {
                 let s0 = t; let e0 = (t + per).min(s.end_ms); t = e0;
-                all.push(WordSpan { start_ms: s0, end_ms: e0, text: tok.to_string() });
+                all.push(WordSpan { start_ms: s0, end_ms: e0, text: tok.to_string(), speaker: s.speaker, confidence: s.confidence, hidden: false });
             }
         }
     }
@@ -417,10 +777,11 @@ fn coalesce_phrases(segments: &[CaptionSegment]) -> Vec<Phrase> {
         if cur.is_empty() { cur.push(w); continue; }
         let prev = cur.last().unwrap();
         let gap = w.start_ms.saturating_sub(prev.end_ms);
-        let hard_break = [".","!","?"].iter().any(|p| prev.text.ends_with(p)) || gap > 350 || cur.len() >= 3;
+        let hard_break = [".","!","?"].iter().any(|p| prev.text.ends_with(p)) || gap > 350 || cur.len() >= 3
+            || prev.speaker != w.speaker; // never mix two speakers in one caption
         if hard_break {
             let tokens = cur.iter().map(|x| x.text.clone()).collect::<Vec<_>>();
-            out.push(Phrase{ start_ms: cur.first().unwrap().start_ms, end_ms: cur.last().unwrap().end_ms, tokens, spans: cur.clone() });
+            out.push(Phrase{ start_ms: cur.first().unwrap().start_ms, end_ms: cur.last().unwrap().end_ms, tokens, speaker: cur[0].speaker, spans: cur.clone() });
             cur = vec![w];
         } else {
             cur.push(w);
@@ -428,7 +789,7 @@ fn coalesce_phrases(segments: &[CaptionSegment]) -> Vec<Phrase> {
     }
     if !cur.is_empty() {
         let tokens = cur.iter().map(|x| x.text.clone()).collect::<Vec<_>>();
-        out.push(Phrase{ start_ms: cur.first().unwrap().start_ms, end_ms: cur.last().unwrap().end_ms, tokens, spans: cur.clone() });
+        out.push(Phrase{ start_ms: cur.first().unwrap().start_ms, end_ms: cur.last().unwrap().end_ms, tokens, speaker: cur[0].speaker, spans: cur.clone() });
     }
     out
 }
@@ -524,13 +885,22 @@ fn split_phrase_for_width(tokens: &[String], spans: &[WordSpan], frame_w: u32, f
     segments
 }
 
+// Which tokens to mark for review (all false unless marking is on)
+fn low_confidence_flags(spans: &[WordSpan], mark: bool) -> Vec<bool> {
+    spans.iter()
+        .map(|w| mark && w.confidence.is_some_and(|c| c < whisper::LOW_CONFIDENCE_THRESHOLD))
+        .collect()
+}
+
 // Color tags use BBGGRR (no alpha) for \1c
 fn bgr_from_aa_bgrr(aa_bgrr: &str) -> String {
     aa_bgrr.trim_start_matches("&H").chars().skip(2).collect() // drop AA
 }
 
+#[allow(clippy::too_many_arguments)]
 fn assemble_colored_two_lines(
     tokens: &[String], hi: usize,
+    flagged: &[bool],
     white_bgr: &str, hi_bgr: &str,
     line1_count: usize,
     header: &str,
@@ -552,8 +922,16 @@ fn assemble_colored_two_lines(
         // Only highlight if hi is a valid index (not usize::MAX)
         let should_highlight = has_highlighting && i == hi;
         s.push_str(if should_highlight { &hi_style } else { &white });
+        // Low-confidence words are underlined, and red unless they're the highlighted word
+        let is_flagged = flagged.get(i).copied().unwrap_or(false);
+        if is_flagged && should_highlight {
+            s.push_str(r"{\u1}");
+        } else if is_flagged {
+            s.push_str(&format!("{{\\u1\\1c&H{}&}}", LOW_CONFIDENCE_BGR));
+        }
         let t = tokens[i].replace('\\', r"\\").replace('{', r"\{").replace('}', r"\}");
         s.push_str(&t);
+        if is_flagged { s.push_str(r"{\u0}"); }
         if i + 1 < tokens.len() { s.push(' '); }
     }
     s
@@ -575,13 +953,12 @@ struct AssStyle {
 fn pct_to_margin_v(frame_h: u32, y_pct_from_top: f32) -> u32 {
     // bottom-aligned: margin_v measured from bottom
     let y = (frame_h as f32 * (y_pct_from_top / 100.0)).round() as i32;
-    let margin_from_bottom = (frame_h as i32 - y).max(0) as u32;
-    margin_from_bottom
+    (frame_h as i32 - y).max(0) as u32
 }
 
-fn stopwords() -> &'static HashSet<&'static str> {
+fn stopwords(language: Option<&str>) -> &'static HashSet<&'static str> {
     use std::sync::LazyLock;
-    static SW: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    static EN: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
         [
             "a","an","the","to","of","in","on","at","by","for","with","and","or","but",
             "i","you","he","she","we","they","be","is","are","was","were","have","has","had",
@@ -590,7 +967,41 @@ fn stopwords() -> &'static HashSet<&'static str> {
             "um","uh","you","know"
         ].into_iter().collect()
     });
-    &SW
+    static ES: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+        [
+            "el","la","los","las","un","una","unos","unas","de","del","al","a","en","con","por","para",
+            "y","o","pero","que","se","lo","le","les","me","te","nos","yo","tú","él","ella","es","son",
+            "está","están","fue","era","ser","estar","hay","muy","más","ya","eh","este","pues","bueno"
+        ].into_iter().collect()
+    });
+    static FR: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+        [
+            "le","la","les","un","une","des","de","du","au","aux","à","en","dans","sur","par","pour",
+            "avec","et","ou","mais","que","qui","je","tu","il","elle","on","nous","vous","ils","elles",
+            "est","sont","était","être","avoir","a","ai","ce","c'est","ça","très","plus","euh","bah","ben"
+        ].into_iter().collect()
+    });
+    static DE: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+        [
+            "der","die","das","den","dem","des","ein","eine","einen","einem","und","oder","aber","zu",
+            "in","im","an","am","auf","mit","von","für","ich","du","er","sie","es","wir","ihr","ist",
+            "sind","war","hat","haben","wird","werden","kann","sehr","noch","schon","also","halt","äh","ähm"
+        ].into_iter().collect()
+    });
+    static PT: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+        [
+            "o","a","os","as","um","uma","de","do","da","dos","das","em","no","na","nos","nas","com",
+            "por","para","e","ou","mas","que","se","eu","você","ele","ela","nós","eles","é","são","foi",
+            "era","ser","estar","tem","muito","mais","já","né","tipo","então","tá"
+        ].into_iter().collect()
+    });
+    match language {
+        Some("es") => &ES,
+        Some("fr") => &FR,
+        Some("de") => &DE,
+        Some("pt") => &PT,
+        _ => &EN,
+    }
 }
 
 fn power_words() -> &'static HashSet<&'static str> {
@@ -609,7 +1020,7 @@ fn build_global_tf(segments: &[CaptionSegment]) -> HashMap<String, u32> {
     for s in segments {
         for w in &s.words {
             let t = w.text.trim();
-            if t.is_empty() { continue; }
+            if t.is_empty() || w.hidden { continue; }
             *tf.entry(t.to_lowercase()).or_insert(0) += 1;
         }
     }
@@ -646,6 +1057,7 @@ fn mean_std(vals: &[f32]) -> (f32, f32) {
 }
 
 struct HighlightState {
+    stopwords: &'static HashSet<&'static str>,
     tf: HashMap<String,u32>,
     recent: VecDeque<(String,u64)>,   // (token_lower, time_ms)
     last_hl_ms: Option<u64>,
@@ -656,7 +1068,9 @@ struct HighlightState {
 
 impl HighlightState {
     fn new(segments: &[CaptionSegment]) -> Self {
+        let language = segments.iter().find_map(|s| s.language.as_deref());
         Self {
+            stopwords: stopwords(language),
             tf: build_global_tf(segments),
             recent: VecDeque::new(),
             last_hl_ms: None,
@@ -690,7 +1104,7 @@ fn choose_highlight_idx(
     phrase_idx: usize,
     st: &mut HighlightState
 ) -> Option<usize> {
-    let sw = stopwords();
+    let sw = st.stopwords;
     let pw = power_words();
 
     // rarity controls
@@ -791,18 +1205,27 @@ fn choose_highlight_idx(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_ass_document(
     w: u32,
     h: u32,
     style: &AssStyle,
     segments: &[CaptionSegment],
+    translation: Option<(&AssStyle, &[CaptionSegment])>,
+    speakers: &[AssStyle],
     karaoke: bool,
-    glow_effect: bool
+    glow_effect: bool,
+    mark_low_confidence: bool
 ) -> Result<String> {
     if segments.is_empty() {
         return Err(anyhow!("No caption segments"));
     }
 
+    let mut styles = ass_style_line("TikTok", style);
+    if let Some((tr_style, _)) = translation {
+        styles.push_str(&ass_style_line("Translation", tr_style));
+    }
+
     let header = format!(
 r#"[Script Info]
 ScriptType: v4.00+
@@ -812,27 +1235,25 @@ ScaledBorderAndShadow: yes
 
 [V4+ Styles]
 Format: Name,Fontname,Fontsize,PrimaryColour,SecondaryColour,OutlineColour,BackColour,Bold,Italic,Underline,StrikeOut,ScaleX,ScaleY,Spacing,Angle,BorderStyle,Outline,Shadow,Alignment,MarginL,MarginR,MarginV,Encoding
-Style: TikTok,{font},{size},{pri},{sec},{out},&H64000000,0,0,0,0,100,100,0,0,1,{ow},{sh},{al},60,60,{mv},1
-
+{styles}
 [Events]
 Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
 "#,
-        w = w, h = h,
-        font = style.font_name, size = style.font_size,
-        pri = style.primary, sec = style.secondary,
-        out = style.outline, ow = style.outline_w, sh = style.shadow,
-        al = style.align, mv = style.margin_v
+        w = w, h = h, styles = styles
     );
 
     let mut lines = String::new();
+    // Each speaker's captions use that speaker's style; unlabeled ones use the main style
+    let style_for = |speaker: Option<u32>| speaker.and_then(|s| speakers.get(s as usize)).unwrap_or(style);
 
     if karaoke {
         let phrases = coalesce_phrases(segments);
-        let white_bgr = bgr_from_aa_bgrr(&style.primary);
-        let hi_bgr    = bgr_from_aa_bgrr(&style.highlight);
 
         // Simple single-line karaoke: split phrases that are too wide, then process each segment
         for ph in phrases {
+            let style = style_for(ph.speaker);
+            let white_bgr = bgr_from_aa_bgrr(&style.primary);
+            let hi_bgr    = bgr_from_aa_bgrr(&style.highlight);
             let tokens_upper = normalize_tokens(&ph.spans);
             let segments = split_phrase_for_width(&tokens_upper, &ph.spans, w, style.font_size);
 
@@ -845,6 +1266,7 @@ Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
             // Process each width-appropriate segment
             for (segment_tokens, segment_spans) in segments {
                 let windows = contiguous_cs_windows(&segment_spans);
+                let flagged = low_confidence_flags(&segment_spans, mark_low_confidence);
 
                 for (i, (cs0, cs1)) in windows.iter().enumerate() {
                 let dur_ms = (cs1 - cs0) * 10;
@@ -867,7 +1289,7 @@ Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
                         6.0,
                         stretch_tag_ms(dur_ms)
                     );
-                    let glow_text = assemble_colored_two_lines(&segment_tokens, i, &white_bgr, &hi_bgr, usize::MAX, &glow_header, style.font_size);
+                    let glow_text = assemble_colored_two_lines(&segment_tokens, i, &flagged, &white_bgr, &hi_bgr, usize::MAX, &glow_header, style.font_size);
                     lines.push_str(&format!(
                         "Dialogue: 0,{},{},TikTok,,0,0,0,,{}\n",
                         cs_to_ass(*cs0), cs_to_ass(*cs1), glow_text
@@ -880,14 +1302,14 @@ Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
                         style.outline_w,
                         stretch_tag_ms(dur_ms)
                     );
-                    let main_text = assemble_colored_two_lines(&segment_tokens, i, &white_bgr, &hi_bgr, usize::MAX, &main_header, style.font_size);
+                    let main_text = assemble_colored_two_lines(&segment_tokens, i, &flagged, &white_bgr, &hi_bgr, usize::MAX, &main_header, style.font_size);
                     lines.push_str(&format!(
                         "Dialogue: 1,{},{},TikTok,,0,0,0,,{}\n",
                         cs_to_ass(*cs0), cs_to_ass(*cs1), main_text
                     ));
                 } else {
                     // Single layer
-                    let text = assemble_colored_two_lines(&segment_tokens, i, &white_bgr, &hi_bgr, usize::MAX, &header, style.font_size);
+                    let text = assemble_colored_two_lines(&segment_tokens, i, &flagged, &white_bgr, &hi_bgr, usize::MAX, &header, style.font_size);
                     lines.push_str(&format!(
                         "Dialogue: 0,{},{},TikTok,,0,0,0,,{}\n",
                         cs_to_ass(*cs0), cs_to_ass(*cs1), text
@@ -897,21 +1319,21 @@ Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
             }
         }
     } else {
-        let white_bgr = bgr_from_aa_bgrr(&style.primary);
-        let hi_bgr    = bgr_from_aa_bgrr(&style.highlight);
         let x = (w/2) as i32;
-        // Calculate Y position based on alignment
-        let y = match style.align {
-            5 => (h / 2) as i32, // Middle center - use actual center of frame
-            _ => (h as i32 - style.margin_v as i32).max(0), // Bottom center - use margin
-        };
-
         let phrases = coalesce_phrases(segments);
 
         // NEW: state for smart highlighting
         let mut hl_state = HighlightState::new(segments);
 
         for (p_idx, phrase) in phrases.iter().enumerate() {
+            let style = style_for(phrase.speaker);
+            let white_bgr = bgr_from_aa_bgrr(&style.primary);
+            let hi_bgr    = bgr_from_aa_bgrr(&style.highlight);
+            // Calculate Y position based on alignment
+            let y = match style.align {
+                5 => (h / 2) as i32, // Middle center - use actual center of frame
+                _ => (h as i32 - style.margin_v as i32).max(0), // Bottom center - use margin
+            };
             let tokens_upper = normalize_tokens(&phrase.spans);
 
             // Split phrase into single-line segments, same as karaoke mode
@@ -929,8 +1351,9 @@ Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
 
                 // Build a ONE-LINE body: only colors/sizes + entrance animation
                 // (no \pos/\bord/\shad in here; those are added by the glow/stroke layers)
+                let flagged = low_confidence_flags(&segment_spans, mark_low_confidence);
                 let text_body = assemble_colored_two_lines(
-                    &segment_tokens, hi_idx, &white_bgr, &hi_bgr,
+                    &segment_tokens, hi_idx, &flagged, &white_bgr, &hi_bgr,
                     usize::MAX,               // no line break
                     &bounce_tag(),            // entrance scale
                     style.font_size
@@ -953,9 +1376,55 @@ Format: Layer,Start,End,Style,Name,MarginL,MarginR,MarginV,Effect,Text
         }
     }
 
+    // Translation lines are plain segment-level text in their own style (wrapping allowed)
+    if let Some((_, tr_segments)) = translation {
+        for seg in tr_segments {
+            let text = seg.text.trim().replace(['{', '}', '\\'], "");
+            if text.is_empty() || seg.end_ms <= seg.start_ms { continue; }
+            lines.push_str(&format!(
+                "Dialogue: 2,{},{},Translation,,0,0,0,,{}\n",
+                cs_to_ass(ms_to_cs(seg.start_ms)), cs_to_ass(ms_to_cs(seg.end_ms)), text
+            ));
+        }
+    }
+
     Ok(header + &lines)
 }
 
+fn ass_style_line(name: &str, style: &AssStyle) -> String {
+    format!(
+        "Style: {name},{font},{size},{pri},{sec},{out},&H64000000,0,0,0,0,100,100,0,0,1,{ow},{sh},{al},60,60,{mv},1\n",
+        name = name,
+        font = style.font_name, size = style.font_size,
+        pri = style.primary, sec = style.secondary,
+        out = style.outline, ow = style.outline_w, sh = style.shadow,
+        al = style.align, mv = style.margin_v
+    )
+}
+
+/// Style for the English line in bilingual mode: smaller, its own color,
+/// top-anchored just under the main captions so longer lines wrap downward
+fn translation_ass_style(main: &AssStyle, frame_h: u32, color: Option<&str>) -> AssStyle {
+    let primary = color.map(hex_to_ass_color).unwrap_or_else(|| "&H0066E0FF".into()); // soft yellow
+    let margin_top = match main.align {
+        5 => frame_h / 2 + main.font_size * 3 / 4,
+        _ => frame_h.saturating_sub(main.margin_v) + main.font_size / 4,
+    };
+
+    AssStyle {
+        font_name: main.font_name.clone(),
+        font_size: (main.font_size * 62 / 100).max(12),
+        primary: primary.clone(),
+        secondary: primary.clone(),
+        outline: main.outline.clone(),
+        outline_w: (main.outline_w * 6 / 10).max(2),
+        shadow: 0,
+        align: 8, // top-center
+        margin_v: margin_top,
+        highlight: primary,
+    }
+}
+
 /// Calculate proportional font size that maintains consistent appearance across different aspect ratios
 /// Uses 9:16 format (608x1080) as the reference size
 /// Formula: font_size = reference_font_size * sqrt(current_area / reference_area)
@@ -978,10 +1447,61 @@ fn calculate_proportional_font_size(frame_w: u32, frame_h: u32) -> u32 {
     font_size.max(18.0) as u32
 }
 
+/// ASS alignment and vertical margin for a caption position: "bottom" (default) or "center"
+fn caption_layout(frame_h: u32, position: Option<&str>) -> (u32, u32) {
+    match position.unwrap_or("bottom") {
+        "center" => (5, 0), // Alignment 5 = middle center, margin_v 0 for center
+        _ => (2, pct_to_margin_v(frame_h, 88.0)), // Alignment 2 = bottom center (default)
+    }
+}
+
+// Text colors for speakers 1, 2, ... when none are given; speaker 0 keeps the main color
+const DEFAULT_SPEAKER_COLORS: &[&str] = &["#7FDBFF", "#FF9ECF", "#B8F5A0", "#FFB870"];
+
+/// One style per speaker found in the segments, derived from the main style
+fn speaker_ass_styles(
+    main: &AssStyle,
+    frame_h: u32,
+    segments: &[CaptionSegment],
+    colors: Option<&[String]>,
+    positions: Option<&[String]>
+) -> Vec<AssStyle> {
+    let count = segments.iter()
+        .flat_map(|s| s.words.iter().map(|w| w.speaker).chain([s.speaker]))
+        .flatten()
+        .max()
+        .map_or(0, |max| max as usize + 1);
+
+    (0..count).map(|i| {
+        let primary = match colors.and_then(|c| c.get(i)) {
+            Some(hex) => hex_to_ass_color(hex),
+            None if i == 0 => main.primary.clone(),
+            None => hex_to_ass_color(DEFAULT_SPEAKER_COLORS[(i - 1) % DEFAULT_SPEAKER_COLORS.len()]),
+        };
+        let (align, margin_v) = match positions.and_then(|p| p.get(i)) {
+            Some(position) => caption_layout(frame_h, Some(position)),
+            None => (main.align, main.margin_v),
+        };
+        AssStyle {
+            font_name: main.font_name.clone(),
+            font_size: main.font_size,
+            primary: primary.clone(),
+            secondary: primary,
+            outline: main.outline.clone(),
+            outline_w: main.outline_w,
+            shadow: main.shadow,
+            align,
+            margin_v,
+            highlight: main.highlight.clone(),
+        }
+    }).collect()
+}
+
 /// Create default ASS style for TikTok-style captions with proportional sizing
 /// Uses 9:16 format as reference to maintain consistent caption size across all formats
 /// Accepts optional color parameters - if None, uses defaults (white text, black outline, yellow highlight)
 /// Position parameter controls vertical alignment: "bottom" (default) or "center"
+#[allow(clippy::too_many_arguments)]
 fn default_ass_style(
     frame_w: u32,
     frame_h: u32,
@@ -997,11 +1517,7 @@ fn default_ass_style(
     let highlight = highlight_color.map(hex_to_ass_color).unwrap_or_else(|| "&H0000FFFE".into());
     let outline = outline_color.map(hex_to_ass_color).unwrap_or_else(|| "&H00000000".into());
 
-    // Determine vertical position and alignment based on position parameter
-    let (align, margin_v) = match position.unwrap_or("bottom") {
-        "center" => (5, 0), // Alignment 5 = middle center, margin_v 0 for center
-        _ => (2, pct_to_margin_v(frame_h, 88.0)), // Alignment 2 = bottom center (default)
-    };
+    let (align, margin_v) = caption_layout(frame_h, position);
 
     AssStyle {
         font_name: font_name.unwrap_or("Montserrat Black").into(),
@@ -1017,6 +1533,42 @@ fn default_ass_style(
     }
 }
 
+// Bundled caption fonts and whether each one has Cyrillic glyphs
+const BUNDLED_FONTS: &[(&str, bool)] = &[
+    ("Montserrat Black", true), ("Roboto Bold", true), ("Oswald Bold", true),
+    ("Bangers Regular", false), ("Kanit Bold", false), ("Komika Axis", false),
+    ("Poppins Black", false), ("THEBOLDFONT", false), ("WorkSans Bold", false),
+];
+
+/// Pick a font that can render the detected language.
+/// Bundled fonts are Latin-only (plus Cyrillic for a few), so other scripts fall back to
+/// a bundled font covering them or to the platform's system font for that script.
+/// User fonts that aren't bundled are left alone - we can't know their coverage.
+fn font_for_language(font_name: Option<&str>, language: Option<&str>) -> Option<String> {
+    let requested = font_name.unwrap_or("Montserrat Black");
+    let Some(&(_, has_cyrillic)) = BUNDLED_FONTS.iter().find(|(name, _)| *name == requested) else {
+        return font_name.map(|f| f.to_string());
+    };
+
+    let (mac, windows, other) = match language.unwrap_or("en") {
+        "ru" | "uk" | "bg" | "sr" | "mk" | "be" | "kk" | "mn" | "tg" => {
+            if has_cyrillic { return font_name.map(|f| f.to_string()); }
+            ("Montserrat Black", "Montserrat Black", "Montserrat Black")
+        }
+        "th" => ("Kanit Bold", "Kanit Bold", "Kanit Bold"),
+        "hi" | "mr" | "ne" | "sa" => ("Poppins Black", "Poppins Black", "Poppins Black"),
+        "zh" | "yue" => ("PingFang SC", "Microsoft YaHei", "Noto Sans CJK SC"),
+        "ja" => ("Hiragino Sans", "Yu Gothic", "Noto Sans CJK JP"),
+        "ko" => ("Apple SD Gothic Neo", "Malgun Gothic", "Noto Sans CJK KR"),
+        "ar" | "fa" | "ur" | "ps" | "sd" => ("Geeza Pro", "Segoe UI", "Noto Sans Arabic"),
+        "he" | "yi" => ("Arial Hebrew", "Segoe UI", "Noto Sans Hebrew"),
+        _ => return font_name.map(|f| f.to_string()),
+    };
+
+    let fallback = if cfg!(target_os = "macos") { mac } else if cfg!(target_os = "windows") { windows } else { other };
+    Some(fallback.to_string())
+}
+
 /// Convert hex color string (e.g., "#ffffff") to ASS color format (e.g., "&H00FFFFFF")
 fn hex_to_ass_color(hex: &str) -> String {
     let hex = hex.trim_start_matches('#');
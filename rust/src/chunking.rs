@@ -0,0 +1,276 @@
+use crate::rpc::RpcEvent;
+use crate::types::{WhisperResponse, WhisperSegment, WhisperWord};
+use futures_util::stream::{self, StreamExt};
+use regex::Regex;
+use std::future::Future;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command as TokioCommand;
+
+// ---- Chunking tuning ----
+pub const CHUNKING_MIN_DURATION: f64 = 300.0; // only chunk recordings longer than 5 min
+const CHUNK_TARGET_SECS: f64 = 150.0;          // preferred chunk length
+const CHUNK_MIN_SECS: f64 = 60.0;              // never cut earlier than this into a chunk
+const CHUNK_MAX_SECS: f64 = 240.0;             // hard cut if no silence is found before this
+const SILENCE_NOISE_DB: i32 = -35;
+const SILENCE_MIN_SECS: f64 = 0.4;
+const WHISPER_CLI_THREADS: usize = 4;          // whisper-cli's default -t
+
+/// A slice of the source audio, in seconds
+#[derive(Debug, Clone, Copy)]
+pub struct Chunk {
+    pub index: usize,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Find silent stretches with ffmpeg's silencedetect filter
+pub async fn detect_silences(audio_path: &str) -> anyhow::Result<Vec<(f64, f64)>> {
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let filter = crate::filtergraph::Filter::new("silencedetect")
+        .opt("noise", format!("{}dB", SILENCE_NOISE_DB))
+        .opt("d", SILENCE_MIN_SECS);
+
+    let output = TokioCommand::new(ffmpeg_path)
+        .arg("-hide_banner")
+        .arg("-i").arg(audio_path)
+        .arg("-af").arg(filter.to_string())
+        .arg("-f").arg("null")
+        .arg("-")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffmpeg silencedetect failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    parse_silencedetect_output(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// Parse `silence_start: 12.3` / `silence_end: 13.1 | silence_duration: 0.8` pairs
+fn parse_silencedetect_output(stderr: &str) -> anyhow::Result<Vec<(f64, f64)>> {
+    let start_re = Regex::new(r"silence_start:\s*(-?[\d.]+)")?;
+    let end_re = Regex::new(r"silence_end:\s*([\d.]+)")?;
+
+    let mut silences = Vec::new();
+    let mut open: Option<f64> = None;
+    for line in stderr.lines() {
+        if let Some(c) = start_re.captures(line) {
+            open = c[1].parse::<f64>().ok().map(|s| s.max(0.0));
+        } else if let Some(c) = end_re.captures(line) {
+            if let (Some(start), Ok(end)) = (open.take(), c[1].parse::<f64>()) {
+                silences.push((start, end));
+            }
+        }
+    }
+    Ok(silences)
+}
+
+/// Cut [0, duration] into chunks of bounded length, preferring the middle of a silence
+/// closest to the target length so no word is split across chunks
+pub fn plan_chunks(duration: f64, silences: &[(f64, f64)]) -> Vec<Chunk> {
+    plan_chunks_between(duration, silences, CHUNK_TARGET_SECS, CHUNK_MIN_SECS, CHUNK_MAX_SECS)
+}
+
+/// Same as plan_chunks, but for an arbitrary maximum chunk length (e.g. an upload size budget)
+pub fn plan_chunks_max(duration: f64, silences: &[(f64, f64)], max_secs: f64) -> Vec<Chunk> {
+    plan_chunks_between(duration, silences, max_secs * 0.75, max_secs * 0.25, max_secs)
+}
+
+fn plan_chunks_between(duration: f64, silences: &[(f64, f64)], target: f64, min: f64, max: f64) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut cursor = 0.0f64;
+
+    while duration - cursor > max {
+        let ideal = cursor + target;
+        let cut = silences.iter()
+            .map(|(s, e)| (s + e) / 2.0)
+            .filter(|mid| *mid >= cursor + min && *mid <= cursor + max)
+            .min_by(|a, b| (a - ideal).abs().total_cmp(&(b - ideal).abs()))
+            .unwrap_or(cursor + max);
+
+        chunks.push(Chunk { index: chunks.len(), start: cursor, end: cut });
+        cursor = cut;
+    }
+    chunks.push(Chunk { index: chunks.len(), start: cursor, end: duration });
+    chunks
+}
+
+/// Write one chunk as 16 kHz mono WAV (what whisper.cpp reads natively)
+async fn extract_chunk(audio_path: &str, chunk: &Chunk, out_path: &Path) -> anyhow::Result<()> {
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let status = TokioCommand::new(ffmpeg_path)
+        .arg("-y").arg("-v").arg("error")
+        .arg("-ss").arg(format!("{:.3}", chunk.start))
+        .arg("-t").arg(format!("{:.3}", chunk.end - chunk.start))
+        .arg("-i").arg(audio_path)
+        .arg("-ar").arg("16000")
+        .arg("-ac").arg("1")
+        .arg("-c:a").arg("pcm_s16le")
+        .arg(out_path)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("ffmpeg failed to extract chunk {} ({:.1}s-{:.1}s)", chunk.index, chunk.start, chunk.end));
+    }
+    Ok(())
+}
+
+async fn extract_and_transcribe<F, Fut>(audio_path: &str, chunk: &Chunk, chunk_path: &Path, transcribe: &F) -> anyhow::Result<WhisperResponse>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<WhisperResponse>>,
+{
+    extract_chunk(audio_path, chunk, chunk_path).await?;
+    transcribe(chunk_path.to_string_lossy().to_string()).await
+}
+
+/// How many whisper-cli processes fit in the machine's thread budget
+pub fn parallel_chunk_jobs() -> usize {
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    (cores / WHISPER_CLI_THREADS).max(1)
+}
+
+/// Split long audio at silences, transcribe the chunks concurrently and stitch the results.
+/// `transcribe` is called with the path of each chunk's WAV file. A chunk without speech is
+/// left as a gap; any other chunk failure is retried once and then fails the whole run.
+pub async fn transcribe_chunked<F, Fut>(
+    id: &str,
+    audio_path: &str,
+    duration: f64,
+    work_dir: &Path,
+    mut emit: impl FnMut(RpcEvent),
+    transcribe: F,
+) -> anyhow::Result<WhisperResponse>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<WhisperResponse>>,
+{
+    let silences = detect_silences(audio_path).await?;
+    let chunks = plan_chunks(duration, &silences);
+    let jobs = parallel_chunk_jobs();
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Split {:.0}s of audio into {} chunks at {} silences, transcribing {} at a time",
+            duration, chunks.len(), silences.len(), jobs)
+    });
+
+    std::fs::create_dir_all(work_dir)?;
+    let total = chunks.len();
+    let transcribe = &transcribe;
+
+    let mut results = stream::iter(chunks.iter().copied())
+        .map(|chunk| async move {
+            let chunk_path = work_dir.join(format!("chunk_{:04}.wav", chunk.index));
+            let mut response = extract_and_transcribe(audio_path, &chunk, &chunk_path, transcribe).await;
+            if matches!(&response, Err(e) if !crate::whisper::is_no_speech(e)) {
+                response = extract_and_transcribe(audio_path, &chunk, &chunk_path, transcribe).await;
+            }
+            let _ = tokio::fs::remove_file(&chunk_path).await;
+            (chunk, response)
+        })
+        .buffer_unordered(jobs);
+
+    let mut done = Vec::with_capacity(total);
+    let mut finished = 0usize;
+    let mut first_error = None;
+    while let Some((chunk, result)) = results.next().await {
+        finished += 1;
+        match result {
+            Ok(response) => done.push((chunk, response)),
+            // A chunk of music or silence legitimately yields no text; keep going
+            Err(e) if crate::whisper::is_no_speech(&e) => {
+                emit(RpcEvent::Log {
+                    id: id.into(),
+                    message: format!("Chunk {} ({:.1}s-{:.1}s) has no speech", chunk.index, chunk.start, chunk.end)
+                });
+                first_error.get_or_insert(e);
+            }
+            // Stitching around a failed chunk would leave a hole in the transcript
+            Err(e) => {
+                return Err(e.context(format!("Chunk {} ({:.1}s-{:.1}s) failed after a retry", chunk.index, chunk.start, chunk.end)));
+            }
+        }
+        emit(RpcEvent::Progress {
+            id: id.into(),
+            status: format!("Transcribed chunk {}/{}", finished, total),
+            progress: finished as f32 / total as f32,
+        });
+    }
+
+    if done.is_empty() {
+        return Err(first_error.unwrap_or_else(|| anyhow::anyhow!("No chunks were transcribed")));
+    }
+
+    done.sort_by_key(|(chunk, _)| chunk.index);
+    Ok(stitch_responses(done, duration))
+}
+
+/// Shift every chunk's timings by its offset and merge into a single response.
+/// Timings past the chunk's own end (hallucinated tails) are dropped.
+pub fn stitch_responses(parts: Vec<(Chunk, WhisperResponse)>, duration: f64) -> WhisperResponse {
+    let mut text = String::new();
+    let mut segments = Vec::new();
+    let mut words = Vec::new();
+    let mut language_votes: Vec<(String, f64)> = Vec::new();
+    let mut task = None;
+
+    for (chunk, response) in parts {
+        task = task.or(response.task.clone());
+        let offset = chunk.start;
+        let length = chunk.end - chunk.start;
+        let language = response.language.as_deref().and_then(crate::whisper::normalize_language_code);
+
+        if let Some(lang) = &language {
+            match language_votes.iter_mut().find(|(l, _)| l == lang) {
+                Some((_, secs)) => *secs += length,
+                None => language_votes.push((lang.clone(), length)),
+            }
+        }
+
+        if !response.text.trim().is_empty() {
+            text.push_str(response.text.trim());
+            text.push(' ');
+        }
+
+        for seg in response.segments.unwrap_or_default() {
+            if seg.start >= length { continue; }
+            segments.push(WhisperSegment {
+                id: segments.len() as u32,
+                start: seg.start + offset,
+                end: seg.end.min(length) + offset,
+                text: seg.text,
+                language: seg.language.or_else(|| language.clone()),
+                speaker: seg.speaker,
+                avg_logprob: seg.avg_logprob,
+            });
+        }
+
+        for word in response.words.unwrap_or_default() {
+            if word.start >= length { continue; }
+            words.push(WhisperWord {
+                word: word.word,
+                start: word.start + offset,
+                end: word.end.min(length) + offset,
+                confidence: word.confidence,
+            });
+        }
+    }
+
+    // The overall language is whatever was spoken for the longest total time
+    let language = language_votes.into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(l, _)| l);
+
+    WhisperResponse {
+        task,
+        language,
+        duration: Some(duration),
+        text: text.trim().to_string(),
+        segments: Some(segments),
+        words: if words.is_empty() { None } else { Some(words) },
+    }
+}
@@ -0,0 +1,351 @@
+use crate::types::{CaptionSegment, FillerCleanupParams, FillerMode, WordSpan};
+
+// Hesitation sounds per language, with repeated letters collapsed ("ummm" → "um")
+const FILLERS: &[(&str, &[&str])] = &[
+    ("en", &["um", "uh", "uhm", "er", "erm", "ah", "hm", "mhm", "m"]),
+    ("de", &["äh", "ähm", "öh", "öhm", "hm", "mhm", "m"]),
+    ("fr", &["euh", "heu", "bah", "hm"]),
+    ("es", &["eh", "em", "ehm", "hm", "m"]),
+    ("it", &["eh", "ehm", "uhm", "mh", "hm"]),
+    ("pt", &["hum", "ahn", "eh", "hm"]),
+    ("nl", &["eh", "ehm", "uh", "uhm", "hm"]),
+    ("ru", &["э", "эм", "хм", "м"]),
+    ("pl", &["y", "yh", "eh", "hm", "m"]),
+];
+
+// Words that are only filler when set off by commas ("it was, like, huge")
+const COMMA_FILLERS: &[(&str, &[&str])] = &[
+    ("en", &["like"]),
+    ("de", &["halt", "quasi", "sozusagen"]),
+    ("fr", &["genre", "bref", "quoi"]),
+    ("es", &["este", "pues"]),
+    ("it", &["cioè", "tipo"]),
+    ("pt", &["tipo", "né"]),
+    ("ru", &["типа", "короче", "ну"]),
+];
+
+// Doubled words that are grammatical ("I had had enough", "die, die ich kenne")
+const ALLOWED_REPEATS: &[(&str, &[&str])] = &[
+    ("en", &["had", "that"]),
+    ("de", &["die", "der", "das"]),
+    ("nl", &["die", "dat"]),
+];
+
+fn list_for(table: &[(&'static str, &'static [&'static str])], language: Option<&str>) -> &'static [&'static str] {
+    let code = language.unwrap_or("en");
+    table.iter().find(|(l, _)| *l == code).map_or(&[], |(_, words)| *words)
+}
+
+fn ends_sentence(text: &str) -> bool {
+    text.trim_end().ends_with(['.', '!', '?', '…'])
+}
+
+// Lowercase, without surrounding punctuation and with repeated letters collapsed
+fn normalize(word: &str) -> String {
+    let mut out = String::new();
+    for c in word.trim_matches(|c: char| !c.is_alphanumeric()).chars().flat_map(char::to_lowercase) {
+        if !out.ends_with(c) { out.push(c); }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flag {
+    Keep,
+    Drop,
+    DropSetOff, // a filler between commas: the comma before it goes too
+}
+
+/// What counts as a disfluency for one caption language
+pub struct Fillers {
+    fillers: Vec<String>,
+    comma_fillers: &'static [&'static str],
+    allowed_repeats: &'static [&'static str],
+}
+
+impl Fillers {
+    pub fn new(language: Option<&str>, extra_words: &[String]) -> Self {
+        let mut fillers: Vec<String> = list_for(FILLERS, language).iter().map(|w| w.to_string()).collect();
+        if language.is_some_and(|l| !FILLERS.iter().any(|(code, _)| *code == l)) {
+            // Hesitations are close to universal; English covers unlisted languages
+            fillers.extend(list_for(FILLERS, None).iter().map(|w| w.to_string()));
+        }
+        fillers.extend(extra_words.iter().map(|w| normalize(w)).filter(|w| !w.is_empty()));
+        Self {
+            fillers,
+            comma_fillers: list_for(COMMA_FILLERS, language),
+            allowed_repeats: list_for(ALLOWED_REPEATS, language),
+        }
+    }
+
+    /// Flags the words to clean up: fillers, stutter fragments ("th-" before "the")
+    /// and immediate repeats ("I, I, I think" keeps the last "I", which is the fluent one)
+    fn flag(&self, words: &[&str]) -> Vec<Flag> {
+        let mut flags = vec![Flag::Keep; words.len()];
+        let mut prev: Option<usize> = None; // last word that stays
+        for (i, word) in words.iter().enumerate() {
+            let norm = normalize(word);
+            if norm.is_empty() { continue; }
+            let prev_text = prev.map(|p| words[p].trim());
+            let after_break = prev_text.is_none_or(|p| p.ends_with(',') || ends_sentence(p));
+            let before_break = word.trim_end().ends_with(',') || ends_sentence(word);
+
+            let set_off = self.comma_fillers.contains(&norm.as_str()) && after_break && before_break
+                && (prev_text.is_some_and(|p| p.ends_with(',')) || word.trim_end().ends_with(','));
+            let filler = set_off || self.fillers.contains(&norm);
+            let fragment = word.trim_end().ends_with('-') && words.get(i + 1).is_some_and(|next| {
+                let stem = word.trim().trim_end_matches('-').to_lowercase();
+                !stem.is_empty() && next.trim().to_lowercase().starts_with(&stem)
+            });
+            let repeat = prev_text.is_some_and(|p| !ends_sentence(p) && normalize(p) == norm)
+                && !self.allowed_repeats.contains(&norm.as_str());
+
+            if repeat {
+                flags[prev.unwrap()] = Flag::Drop;
+                prev = Some(i);
+            } else if filler || fragment {
+                flags[i] = if set_off { Flag::DropSetOff } else { Flag::Drop };
+            } else {
+                prev = Some(i);
+            }
+        }
+        flags
+    }
+}
+
+// A caption unit with its own timing: a word inside a segment, or a one-word segment
+trait Timed {
+    fn text(&self) -> &str;
+    fn text_mut(&mut self) -> &mut String;
+    fn start_ms(&self) -> u64;
+    fn end_ms(&self) -> u64;
+    fn set_end_ms(&mut self, end_ms: u64);
+    fn speaker(&self) -> Option<u32>;
+    fn hide(&mut self);
+}
+
+impl Timed for WordSpan {
+    fn text(&self) -> &str { &self.text }
+    fn text_mut(&mut self) -> &mut String { &mut self.text }
+    fn start_ms(&self) -> u64 { self.start_ms }
+    fn end_ms(&self) -> u64 { self.end_ms }
+    fn set_end_ms(&mut self, end_ms: u64) { self.end_ms = end_ms; }
+    fn speaker(&self) -> Option<u32> { self.speaker }
+    fn hide(&mut self) { self.hidden = true; }
+}
+
+impl Timed for CaptionSegment {
+    fn text(&self) -> &str { &self.text }
+    fn text_mut(&mut self) -> &mut String { &mut self.text }
+    fn start_ms(&self) -> u64 { self.start_ms }
+    fn end_ms(&self) -> u64 { self.end_ms }
+    fn set_end_ms(&mut self, end_ms: u64) { self.end_ms = end_ms; }
+    fn speaker(&self) -> Option<u32> { self.speaker }
+    fn hide(&mut self) { self.hidden = true; }
+}
+
+// A gap longer than this starts a new caption phrase, so a filler after it isn't absorbed
+const ABSORB_GAP_MS: u64 = 350;
+
+/// Hide or remove flagged items. Removed time goes to the word before, so karaoke
+/// windows stay contiguous; a closing "." moves there too and a sentence-initial
+/// capital moves to the word after.
+fn apply<T: Timed>(items: &mut Vec<T>, flags: &[Flag], mode: FillerMode) -> usize {
+    let count = flags.iter().filter(|f| **f != Flag::Keep).count();
+    if count == 0 {
+        return 0;
+    }
+    if mode == FillerMode::Hide {
+        for (item, _) in items.iter_mut().zip(flags).filter(|(_, f)| **f != Flag::Keep) {
+            item.hide();
+        }
+        return count;
+    }
+
+    let mut kept: Vec<T> = Vec::with_capacity(items.len() - count);
+    let mut capitalize_next = false;
+    for (item, &flag) in std::mem::take(items).into_iter().zip(flags) {
+        if flag == Flag::Keep {
+            let mut item = item;
+            if std::mem::take(&mut capitalize_next) {
+                *item.text_mut() = capitalize(item.text());
+            }
+            kept.push(item);
+            continue;
+        }
+
+        let sentence_start = kept.last().is_none_or(|p| ends_sentence(p.text()));
+        capitalize_next |= sentence_start && item.text().trim().starts_with(char::is_uppercase);
+        if let Some(prev) = kept.last_mut().filter(|p| {
+            p.speaker() == item.speaker() && item.start_ms().saturating_sub(p.end_ms()) <= ABSORB_GAP_MS
+        }) {
+            prev.set_end_ms(prev.end_ms().max(item.end_ms()));
+            if flag == Flag::DropSetOff && prev.text().ends_with(',') {
+                let text = prev.text().trim_end_matches(',').to_string();
+                *prev.text_mut() = text;
+            }
+            if ends_sentence(item.text()) && !ends_sentence(prev.text()) {
+                let closing: String = item.text().trim_end().chars().rev().take_while(|c| matches!(c, '.' | '!' | '?' | '…')).collect();
+                let text = prev.text().trim_end().trim_end_matches([',', ';', ':']).to_string();
+                *prev.text_mut() = text + &closing.chars().rev().collect::<String>();
+            }
+        }
+    }
+    *items = kept;
+    count
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Untimed caption text: flagged words are simply dropped, whatever the mode
+fn clean_text(text: &str, fillers: &Fillers) -> (String, usize) {
+    let words: Vec<&str> = text.split(' ').filter(|w| !w.is_empty()).collect();
+    let flags = fillers.flag(&words);
+    let mut pieces: Vec<WordSpan> = words.iter()
+        .map(|w| WordSpan { start_ms: 0, end_ms: 0, text: w.to_string(), speaker: None, confidence: None, hidden: false })
+        .collect();
+    let count = apply(&mut pieces, &flags, FillerMode::Remove);
+    (pieces.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "), count)
+}
+
+/// Remove or hide filler words and stutter repeats in captions. Returns how many words were cleaned up.
+pub fn clean_segments(segments: &mut Vec<CaptionSegment>, params: &FillerCleanupParams, language: Option<&str>) -> usize {
+    let fillers = Fillers::new(language, &params.extra_words);
+
+    // Word-by-word captions: each segment is one timed word
+    let one_word_each = segments.iter().all(|s| s.words.is_empty() && !s.text.trim().contains(' '));
+    if one_word_each {
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        let flags = fillers.flag(&texts);
+        return apply(segments, &flags, params.mode);
+    }
+
+    let mut count = 0;
+    for seg in segments.iter_mut() {
+        if seg.words.is_empty() {
+            let (text, n) = clean_text(&seg.text, &fillers);
+            seg.text = text;
+            count += n;
+            continue;
+        }
+        let texts: Vec<&str> = seg.words.iter().map(|w| w.text.as_str()).collect();
+        let flags = fillers.flag(&texts);
+        let n = apply(&mut seg.words, &flags, params.mode);
+        if n > 0 && params.mode == FillerMode::Remove {
+            seg.text = seg.words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+        }
+        count += n;
+    }
+    segments.retain(|s| !s.text.trim().is_empty());
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Flag::{Drop, DropSetOff, Keep};
+
+    fn flag(text: &str) -> Vec<Flag> {
+        let words: Vec<&str> = text.split(' ').collect();
+        Fillers::new(Some("en"), &[]).flag(&words)
+    }
+
+    fn clean(text: &str) -> String {
+        clean_text(text, &Fillers::new(Some("en"), &[])).0
+    }
+
+    fn word(text: &str, start_ms: u64, end_ms: u64) -> WordSpan {
+        WordSpan { start_ms, end_ms, text: text.into(), speaker: None, confidence: None, hidden: false }
+    }
+
+    #[test]
+    fn stutter_keeps_the_last_repeat() {
+        assert_eq!(flag("I, I, I think"), [Drop, Drop, Keep, Keep]);
+        assert_eq!(clean("I, I, I think so."), "I think so.");
+    }
+
+    #[test]
+    fn grammatical_repeats_are_kept() {
+        assert_eq!(flag("I had had enough"), [Keep; 4]);
+        let words = ["die", "die", "ich", "kenne"];
+        assert_eq!(Fillers::new(Some("de"), &[]).flag(&words), [Keep; 4]);
+    }
+
+    #[test]
+    fn repeats_across_sentences_are_kept() {
+        assert_eq!(flag("Go. Go now"), [Keep; 3]);
+    }
+
+    #[test]
+    fn like_only_counts_when_set_off_by_commas() {
+        assert_eq!(flag("it was, like, huge"), [Keep, Keep, DropSetOff, Keep]);
+        assert_eq!(flag("I like cats"), [Keep; 3]);
+        assert_eq!(clean("It was, like, huge."), "It was huge.");
+    }
+
+    #[test]
+    fn hesitations_and_fragments_are_flagged() {
+        assert_eq!(flag("so ummm th- the plan"), [Keep, Drop, Drop, Keep, Keep]);
+        assert_eq!(flag("a re- plan"), [Keep, Keep, Keep]);
+        let words = ["basically", "yes"];
+        assert_eq!(Fillers::new(Some("en"), &["Basically".into()]).flag(&words), [Drop, Keep]);
+    }
+
+    #[test]
+    fn unlisted_languages_use_english_hesitations() {
+        let words = ["um", "sí"];
+        assert_eq!(Fillers::new(Some("ca"), &[]).flag(&words), [Drop, Keep]);
+    }
+
+    #[test]
+    fn sentence_case_is_repaired() {
+        assert_eq!(clean("Um, so we went."), "So we went.");
+        assert_eq!(clean("We went um."), "We went.");
+        assert_eq!(clean("Done. Uh, next one"), "Done. Next one");
+    }
+
+    #[test]
+    fn removed_time_goes_to_the_previous_word() {
+        let mut words = vec![word("I", 0, 100), word("um", 150, 300), word("think", 300, 500)];
+        assert_eq!(apply(&mut words, &[Keep, Drop, Keep], FillerMode::Remove), 1);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].end_ms, 300);
+        assert_eq!(words[1].start_ms, 300);
+    }
+
+    #[test]
+    fn fillers_after_a_long_pause_are_not_absorbed() {
+        let mut words = vec![word("I", 0, 100), word("um", 1000, 1200), word("think", 1200, 1400)];
+        apply(&mut words, &[Keep, Drop, Keep], FillerMode::Remove);
+        assert_eq!(words[0].end_ms, 100);
+    }
+
+    #[test]
+    fn hide_mode_keeps_words_and_timing() {
+        let mut words = vec![word("uh", 0, 100), word("yes", 100, 200)];
+        assert_eq!(apply(&mut words, &[Drop, Keep], FillerMode::Hide), 1);
+        assert!(words[0].hidden && !words[1].hidden);
+        assert_eq!((words[0].text.as_str(), words[0].end_ms), ("uh", 100));
+    }
+
+    #[test]
+    fn clean_segments_handles_word_per_segment_captions() {
+        let mut segments: Vec<CaptionSegment> = [("So", 0, 100), ("uh", 100, 200), ("yes.", 200, 300)].iter()
+            .map(|&(text, start_ms, end_ms)| CaptionSegment {
+                start_ms, end_ms, text: text.into(), words: Vec::new(),
+                language: None, speaker: None, confidence: None, hidden: false,
+            })
+            .collect();
+        let count = clean_segments(&mut segments, &FillerCleanupParams::default(), Some("en"));
+        assert_eq!(count, 1);
+        let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["So", "yes."]);
+        assert_eq!(segments[0].end_ms, 200);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+/// A single FFmpeg filter (e.g. `scale=1080:1920:flags=lanczos`)
+/// Option values are stored raw and escaped only when the graph is rendered
+#[derive(Debug, Clone)]
+pub struct Filter {
+    name: String,
+    args: Vec<(Option<String>, String)>, // (key, value) - key is None for positional args
+}
+
+impl Filter {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), args: Vec::new() }
+    }
+
+    /// Add a positional argument (e.g. the `1080` in `scale=1080:1920`)
+    pub fn arg(mut self, value: impl ToString) -> Self {
+        self.args.push((None, value.to_string()));
+        self
+    }
+
+    /// Add a named option (e.g. `flags=lanczos`)
+    pub fn opt(mut self, key: &str, value: impl ToString) -> Self {
+        self.args.push((Some(key.to_string()), value.to_string()));
+        self
+    }
+
+    /// Add a named option only when a value is present
+    pub fn opt_if(self, key: &str, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(v) => self.opt(key, v),
+            None => self,
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        for (i, (key, value)) in self.args.iter().enumerate() {
+            f.write_str(if i == 0 { "=" } else { ":" })?;
+            if let Some(key) = key {
+                write!(f, "{}=", key)?;
+            }
+            // Value is escaped twice: once for the option parser, once for the graph parser
+            f.write_str(&escape_graph_level(&escape_option_value(value)))?;
+        }
+        Ok(())
+    }
+}
+
+/// A linear chain of filters joined with commas (what `-vf` / `-af` expect)
+#[derive(Debug, Clone, Default)]
+pub struct FilterChain {
+    filters: Vec<Filter>,
+}
+
+impl FilterChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, filter: Filter) -> &mut Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn then(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+impl fmt::Display for FilterChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 { f.write_str(",")?; }
+            write!(f, "{}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+/// First level: escape a value for the filter option parser.
+/// `:` separates options, `\` and `'` are quoting characters.
+/// Leading/trailing whitespace is escaped too, otherwise av_get_token trims it.
+pub fn escape_option_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 8);
+    let last = value.chars().count().saturating_sub(1);
+    for (i, c) in value.chars().enumerate() {
+        let edge_space = c.is_whitespace() && (i == 0 || i == last);
+        if matches!(c, '\\' | '\'' | ':') || edge_space {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Second level: escape an already option-escaped string for the filtergraph parser.
+/// `[` `]` `,` `;` separate links/filters/chains, `\` and `'` are quoting characters.
+/// The graph parser trims whitespace around the filter arguments as well, so edges are escaped again.
+pub fn escape_graph_level(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 8);
+    let last = value.chars().count().saturating_sub(1);
+    for (i, c) in value.chars().enumerate() {
+        let edge_space = c.is_whitespace() && (i == 0 || i == last);
+        if matches!(c, '\\' | '\'' | '[' | ']' | ',' | ';') || edge_space {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mirror of FFmpeg's av_get_token: skips leading whitespace, stops at an unescaped
+    /// terminator and trims trailing whitespace that was not escaped or quoted
+    fn get_token<'a>(input: &'a str, term: &str) -> (String, &'a str) {
+        let input = input.trim_start();
+        let mut out = String::new();
+        let mut keep = 0;
+        let mut chars = input.char_indices().peekable();
+        let mut rest = "";
+        while let Some((i, c)) = chars.next() {
+            if term.contains(c) {
+                rest = &input[i..];
+                break;
+            }
+            match c {
+                '\\' => {
+                    if let Some((_, next)) = chars.next() {
+                        out.push(next);
+                        keep = out.len();
+                    }
+                }
+                '\'' => {
+                    for (_, q) in chars.by_ref() {
+                        if q == '\'' {
+                            break;
+                        }
+                        out.push(q);
+                    }
+                    keep = out.len();
+                }
+                _ => out.push(c),
+            }
+        }
+        let trimmed = out[keep..].trim_end().len();
+        out.truncate(keep + trimmed);
+        (out, rest)
+    }
+
+    /// Parse a rendered single-option filter back into its value, the way FFmpeg would
+    fn round_trip(filter: &Filter, key: &str) -> String {
+        let rendered = filter.to_string();
+        let (name, args) = rendered.split_once('=').unwrap();
+        let (args, rest) = get_token(args, "[],;");
+        assert!(rest.is_empty(), "graph parser stopped early at {:?}", rest);
+        let value = args.strip_prefix(&format!("{}=", key)).unwrap_or_else(|| panic!("{} has no {} option", name, key));
+        let (value, rest) = get_token(value, ":");
+        assert!(rest.is_empty(), "option parser stopped early at {:?}", rest);
+        value
+    }
+
+    #[test]
+    fn special_characters_survive_both_parsers() {
+        let path = r"C:\Users\me\it's [final], v2; take:3.srt";
+        assert_eq!(round_trip(&Filter::new("subtitles").opt("filename", path), "filename"), path);
+    }
+
+    #[test]
+    fn padding_spaces_survive_both_parsers() {
+        for value in ["  padded text  ", " lead", "trail ", " "] {
+            assert_eq!(round_trip(&Filter::new("drawtext").opt("text", value), "text"), value);
+        }
+    }
+
+    #[test]
+    fn chain_joins_filters_with_commas() {
+        let chain = FilterChain::new()
+            .then(Filter::new("scale").arg(1080).arg(1920).opt("flags", "lanczos"))
+            .then(Filter::new("setsar").arg(1));
+        assert_eq!(chain.to_string(), "scale=1080:1920:flags=lanczos,setsar=1");
+    }
+}
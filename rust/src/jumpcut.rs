@@ -0,0 +1,197 @@
+use crate::filtergraph::{Filter, FilterChain};
+use crate::types::{CaptionSegment, JumpCutParams, TimeRange, TranscribeSegmentsResult};
+
+const DEFAULT_MIN_PAUSE_MS: u64 = 700;
+const DEFAULT_PADDING_MS: u64 = 150;
+const MIN_CUT_MS: u64 = 150; // a shorter cut is a visible jump for no real gain
+
+/// Source ranges to cut: every pause between words longer than the threshold, minus some
+/// padding on either side, plus leading and trailing silence. With `silences` (seconds, from
+/// silencedetect) only the parts of a pause that are actually quiet are cut.
+pub fn plan_cuts(
+    segments: &[CaptionSegment],
+    duration_ms: u64,
+    params: &JumpCutParams,
+    silences: Option<&[(f64, f64)]>,
+) -> Vec<TimeRange> {
+    let min_pause = params.min_pause_ms.unwrap_or(DEFAULT_MIN_PAUSE_MS);
+    let padding = params.padding_ms.unwrap_or(DEFAULT_PADDING_MS);
+
+    let mut speech: Vec<(u64, u64)> = segments.iter()
+        .flat_map(|s| {
+            if s.words.is_empty() {
+                vec![(s.start_ms, s.end_ms)]
+            } else {
+                s.words.iter().map(|w| (w.start_ms, w.end_ms)).collect()
+            }
+        })
+        .filter(|(start, end)| end > start)
+        .collect();
+    if speech.is_empty() {
+        return Vec::new(); // nothing to go by; never cut the whole video
+    }
+    speech.sort_unstable();
+
+    // Speech separated by less than the threshold is one block
+    let mut blocks: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in speech {
+        match blocks.last_mut() {
+            Some(last) if start <= last.1 + min_pause => last.1 = last.1.max(end),
+            _ => blocks.push((start, end)),
+        }
+    }
+
+    let mut candidates = Vec::new();
+    let first = blocks[0].0;
+    if first > min_pause {
+        candidates.push((0, first - padding.min(first)));
+    }
+    for pair in blocks.windows(2) {
+        candidates.push((pair[0].1 + padding, pair[1].0.saturating_sub(padding)));
+    }
+    let last = blocks[blocks.len() - 1].1;
+    if duration_ms > last + min_pause {
+        candidates.push((last + padding, duration_ms));
+    }
+
+    let mut cuts: Vec<TimeRange> = Vec::new();
+    for (start, end) in candidates {
+        match silences {
+            None => cuts.push(TimeRange { start_ms: start, end_ms: end }),
+            Some(silences) => cuts.extend(silences.iter().map(|(s, e)| TimeRange {
+                start_ms: start.max((s * 1000.0) as u64),
+                end_ms: end.min((e * 1000.0) as u64),
+            })),
+        }
+    }
+    cuts.retain(|c| c.end_ms >= c.start_ms + MIN_CUT_MS);
+    cuts.sort_by_key(|c| c.start_ms);
+    cuts
+}
+
+/// Where a source time ends up in the edited video; a time inside a cut lands on the cut
+pub fn remap_ms(t: u64, cuts: &[TimeRange]) -> u64 {
+    let mut removed = 0;
+    for cut in cuts {
+        if t >= cut.end_ms {
+            removed += cut.end_ms - cut.start_ms;
+        } else {
+            if t > cut.start_ms {
+                removed += t - cut.start_ms;
+            }
+            break;
+        }
+    }
+    t - removed
+}
+
+/// Shift caption timings onto the edited timeline, dropping captions that were cut entirely
+pub fn remap_segments(segments: &mut Vec<CaptionSegment>, cuts: &[TimeRange]) {
+    for seg in segments.iter_mut() {
+        seg.start_ms = remap_ms(seg.start_ms, cuts);
+        seg.end_ms = remap_ms(seg.end_ms, cuts);
+        for word in &mut seg.words {
+            word.start_ms = remap_ms(word.start_ms, cuts);
+            word.end_ms = remap_ms(word.end_ms, cuts);
+        }
+        seg.words.retain(|w| w.end_ms > w.start_ms);
+    }
+    segments.retain(|s| s.end_ms > s.start_ms);
+}
+
+/// Move a whole transcription onto the edited timeline
+pub fn remap_transcription(transcription: &mut TranscribeSegmentsResult, cuts: &[TimeRange]) {
+    remap_segments(&mut transcription.segments, cuts);
+    for word in &mut transcription.low_confidence_words {
+        word.start_ms = remap_ms(word.start_ms, cuts);
+        word.end_ms = remap_ms(word.end_ms, cuts);
+    }
+    transcription.low_confidence_words.retain(|w| w.end_ms > w.start_ms);
+    if let Some(duration) = transcription.duration.as_mut() {
+        *duration = remap_ms((*duration * 1000.0) as u64, cuts) as f64 / 1000.0;
+    }
+}
+
+/// The source ranges that stay, in order
+pub fn keep_ranges(cuts: &[TimeRange], duration_ms: u64) -> Vec<TimeRange> {
+    let mut keeps = Vec::with_capacity(cuts.len() + 1);
+    let mut pos = 0;
+    for cut in cuts {
+        if cut.start_ms > pos {
+            keeps.push(TimeRange { start_ms: pos, end_ms: cut.start_ms });
+        }
+        pos = pos.max(cut.end_ms);
+    }
+    if duration_ms > pos {
+        keeps.push(TimeRange { start_ms: pos, end_ms: duration_ms });
+    }
+    keeps
+}
+
+fn secs(ms: f64) -> String {
+    format!("{:.3}", ms / 1000.0)
+}
+
+// Feed every piece its own copy of the input stream; returns the pad to read piece i from
+fn split_input(graph: &mut Vec<String>, input: &str, split: &str, prefix: &str, count: usize) -> Vec<String> {
+    if count == 1 {
+        return vec![input.to_string()];
+    }
+    let pads: Vec<String> = (0..count).map(|i| format!("[{}{}]", prefix, i)).collect();
+    graph.push(format!("{}{}{}", input, Filter::new(split).arg(count), pads.concat()));
+    pads
+}
+
+/// `-filter_complex` graph that joins the kept ranges of the video and audio input pads
+/// (e.g. `[0:v:0]`) into `[vout]` and `[aout]`. Each piece is trimmed on its own timestamps,
+/// so variable frame rate input stays in sync. Each audio piece reaches half a crossfade into
+/// the cuts on either side, so the overlapping crossfades leave the audio exactly as long as the video.
+pub fn edit_graph(keeps: &[TimeRange], video_in: Option<&str>, audio_in: Option<&str>, crossfade_ms: u64) -> String {
+    let count = keeps.len();
+    let mut graph = Vec::new();
+
+    if let Some(video_in) = video_in {
+        let inputs = split_input(&mut graph, video_in, "split", "sv", count);
+        let mut pieces = String::new();
+        for (i, (keep, input)) in keeps.iter().zip(&inputs).enumerate() {
+            let piece = if count == 1 { "[vout]".to_string() } else { format!("[v{}]", i) };
+            let chain = FilterChain::new()
+                .then(Filter::new("trim").opt("start", secs(keep.start_ms as f64)).opt("end", secs(keep.end_ms as f64)))
+                .then(Filter::new("setpts").arg("PTS-STARTPTS"));
+            graph.push(format!("{}{}{}", input, chain, piece));
+            pieces.push_str(&piece);
+        }
+        if count > 1 {
+            graph.push(format!("{}{}[vout]", pieces, Filter::new("concat").opt("n", count).opt("v", 1).opt("a", 0)));
+        }
+    }
+
+    if let Some(audio_in) = audio_in {
+        let inputs = split_input(&mut graph, audio_in, "asplit", "sa", count);
+        let half = crossfade_ms as f64 / 2.0;
+        let mut pieces = Vec::with_capacity(count);
+        for (i, (keep, input)) in keeps.iter().zip(&inputs).enumerate() {
+            let start = if i > 0 { (keep.start_ms as f64 - half).max(0.0) } else { keep.start_ms as f64 };
+            let end = if i + 1 < count { keep.end_ms as f64 + half } else { keep.end_ms as f64 };
+            let piece = if count == 1 { "[aout]".to_string() } else { format!("[a{}]", i) };
+            let chain = FilterChain::new()
+                .then(Filter::new("atrim").opt("start", secs(start)).opt("end", secs(end)))
+                .then(Filter::new("asetpts").arg("PTS-STARTPTS"));
+            graph.push(format!("{}{}{}", input, chain, piece));
+            pieces.push(piece);
+        }
+        if count > 1 && crossfade_ms == 0 {
+            graph.push(format!("{}{}[aout]", pieces.concat(), Filter::new("concat").opt("n", count).opt("v", 0).opt("a", 1)));
+        } else if count > 1 {
+            let fade = Filter::new("acrossfade").opt("d", secs(crossfade_ms as f64)).opt("c1", "tri").opt("c2", "tri");
+            let mut joined = pieces[0].clone();
+            for (i, piece) in pieces.iter().enumerate().skip(1) {
+                let out = if i + 1 == count { "[aout]".to_string() } else { format!("[ax{}]", i) };
+                graph.push(format!("{}{}{}{}", joined, piece, fade, out));
+                joined = out;
+            }
+        }
+    }
+
+    graph.join(";")
+}
@@ -2,5 +2,21 @@ pub mod rpc;
 pub mod types;
 pub mod audio;
 pub mod video;
+pub mod filtergraph;
 pub mod captions;
-pub mod whisper;
\ No newline at end of file
+pub mod whisper;
+pub mod whisper_server;
+pub mod models;
+pub mod cache;
+pub mod chunking;
+pub mod backend;
+pub mod vocabulary;
+pub mod alignment;
+pub mod subtitles;
+pub mod multitrack;
+pub mod redaction;
+pub mod fillers;
+pub mod jumpcut;
+pub mod textedit;
+#[cfg(feature = "native-whisper")]
+pub mod whisper_native;
@@ -0,0 +1,657 @@
+use crate::rpc::RpcEvent;
+use crate::types::{
+    CustomModel, CustomModelStore, DeleteModelParams, DeleteModelResult, DownloadModelParams, DownloadModelResult,
+    ImportModelParams, ModelChecksum, ModelChecksumIndex, ModelInfo, ModelLanguages, ModelQuantization, RegisterModelParams,
+};
+use futures_util::StreamExt;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use ModelLanguages::{English as EN, Multilingual as ALL};
+use ModelQuantization::{F16, Q5_0, Q5_1, Q8_0};
+
+/// A whisper.cpp model that can be downloaded by name
+#[derive(Debug)]
+pub struct ModelSpec {
+    pub name: &'static str,               // Also the file name: ggml-<name>.bin
+    pub family: &'static str,             // Size class, for fallbacks
+    pub size_mb: u32,                     // Approximate download size in MiB
+    pub languages: ModelLanguages,
+    pub quantization: ModelQuantization,
+    pub sha1: Option<&'static str>,       // From whisper.cpp's models/README.md; None = not verified
+    pub base_url: Option<&'static str>,   // Hosted outside whisper.cpp's own repo
+}
+
+const fn spec(
+    name: &'static str,
+    family: &'static str,
+    size_mb: u32,
+    languages: ModelLanguages,
+    quantization: ModelQuantization,
+    sha1: Option<&'static str>,
+) -> ModelSpec {
+    ModelSpec { name, family, size_mb, languages, quantization, sha1, base_url: None }
+}
+
+impl ModelSpec {
+    const fn hosted_at(self, base_url: &'static str) -> Self {
+        ModelSpec { base_url: Some(base_url), ..self }
+    }
+}
+
+// Every model the app knows how to download. whisper.cpp publishes 5-bit weights of the
+// small models as q5_1 and of the larger ones as q5_0.
+pub const MODEL_REGISTRY: &[ModelSpec] = &[
+    spec("tiny", "tiny", 75, ALL, F16, Some("bd577a113a864445d4c299885e0cb97d4ba92b5f")),
+    spec("tiny-q5_1", "tiny", 31, ALL, Q5_1, Some("2827a03e495b1ed3048ef28a6a4620537db4ee51")),
+    spec("tiny-q8_0", "tiny", 42, ALL, Q8_0, Some("19e8118f6652a650569f5a949d962154e01571d9")),
+    spec("tiny.en", "tiny", 75, EN, F16, Some("c78c86eb1a8faa21b369bcd33207cc90d64ae9df")),
+    spec("tiny.en-q5_1", "tiny", 31, EN, Q5_1, Some("3fb92ec865cbbc769f08137f22470d6b66e071b6")),
+    spec("tiny.en-q8_0", "tiny", 42, EN, Q8_0, Some("802d6668e7d411123e672abe4cb6c18f12306abb")),
+    spec("base", "base", 142, ALL, F16, Some("465707469ff3a37a2b9b8d8f89f2f99de7299dac")),
+    spec("base-q5_1", "base", 57, ALL, Q5_1, Some("a3733eda680ef76256db5fc5dd9de8629e62c5e7")),
+    spec("base-q8_0", "base", 78, ALL, Q8_0, Some("7bb89bb49ed6955013b166f1b6a6c04584a20fbe")),
+    spec("base.en", "base", 142, EN, F16, Some("137c40403d78fd54d454da0f9bd998f78703390c")),
+    spec("base.en-q5_1", "base", 57, EN, Q5_1, Some("d26d7ce5a1b6e57bea5d0431b9c20ae49423c94a")),
+    spec("base.en-q8_0", "base", 78, EN, Q8_0, Some("bb1574182e9b924452bf0cd1510ac034d323e948")),
+    spec("small", "small", 466, ALL, F16, Some("55356645c2b361a969dfd0ef2c5a50d530afd8d5")),
+    spec("small-q5_1", "small", 181, ALL, Q5_1, Some("6fe57ddcfdd1c6b07cdcc73aaf620810ce5fc771")),
+    spec("small-q8_0", "small", 252, ALL, Q8_0, Some("bcad8a2083f4e53d648d586b7dbc0cd673d8afad")),
+    spec("small.en", "small", 466, EN, F16, Some("db8a495a91d927739e50b3fc1cc4c6b8f6c2d022")),
+    spec("small.en-q5_1", "small", 181, EN, Q5_1, Some("20f54878d608f94e4a8ee3ae56016571d47cba34")),
+    spec("small.en-q8_0", "small", 252, EN, Q8_0, Some("9d75ff4ccfa0a8217870d7405cf8cef0a5579852")),
+    spec("small.en-tdrz", "small", 465, EN, F16, Some("b6c6e7e89af1a35c08e6de56b66ca6a02a2fdfa1"))
+        .hosted_at(TINYDIARIZE_URL),
+    spec("medium", "medium", 1533, ALL, F16, Some("fd9727b6e1217c2f614f9b698455c4ffd82463b4")),
+    spec("medium-q5_0", "medium", 514, ALL, Q5_0, Some("7718d4c1ec62ca96998f058114db418236937276")),
+    spec("medium-q8_0", "medium", 785, ALL, Q8_0, Some("e66645948aff4bebbec71b3485c576f3d63af5d6")),
+    spec("medium.en", "medium", 1533, EN, F16, Some("8c30f0e44ce9560643ebd10bbe50cd20eafd3723")),
+    spec("medium.en-q5_0", "medium", 514, EN, Q5_0, Some("bb3b5281bddd61605d6fc76bc5b92d8f20284c3b")),
+    spec("medium.en-q8_0", "medium", 785, EN, Q8_0, Some("b1cf48c12c807e14881f634fb7b6c6ca867f6b38")),
+    spec("large-v3", "large", 2952, ALL, F16, Some("ad82bf6a9043ceed055076d0fd39f5f186ff8062")),
+    spec("large-v3-q5_0", "large", 1080, ALL, Q5_0, Some("e6e2ed78495d403bef4b7cff42ef4aaadcfea8de")),
+    spec("large-v3-turbo", "large", 1549, ALL, F16, Some("4af2b29d7ec73d781377bfd1758ca957a807e941")),
+    spec("large-v3-turbo-q5_0", "large", 547, ALL, Q5_0, Some("e050f7970618a659205450ad97eb95a18d69c9ee")),
+    spec("large-v3-turbo-q8_0", "large", 834, ALL, Q8_0, Some("01bf15bedffe9f39d65c1b6ff9b687ea91f59e0e")),
+];
+
+// Older names that still work in requests
+const MODEL_ALIASES: &[(&str, &str)] = &[("large", "large-v3")];
+
+// What to try when a model isn't installed, by family
+const FAMILY_FALLBACKS: &[(&str, &[&str])] = &[
+    ("large", &["medium", "base", "tiny"]),
+    ("medium", &["base", "tiny"]),
+    ("small", &["base", "tiny"]),
+    ("base", &["tiny"]),
+    ("tiny", &[]),
+];
+
+const DEFAULT_MIRROR_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+const TINYDIARIZE_URL: &str = "https://huggingface.co/akashmjn/tinydiarize-whisper.cpp/resolve/main";
+const GGML_MAGIC: u32 = 0x67676d6c; // "ggml", stored little-endian at the start of every model file
+const MIN_MODEL_BYTES: u64 = 1024 * 1024; // the smallest quantized tiny model is ~30 MB
+
+const PARTIAL_SUFFIX: &str = ".part";
+const CHECKSUM_INDEX_FILE: &str = "checksums.json";
+const CUSTOM_MODELS_FILE: &str = "custom_models.json";
+const PROGRESS_STEP_BYTES: u64 = 1024 * 1024; // one progress event per MB is plenty
+
+impl ModelSpec {
+    pub fn file_name(&self) -> String {
+        format!("ggml-{}.bin", self.name)
+    }
+
+    /// Where to fetch the file, from a mirror that serves the same file names, or from the
+    /// huggingface.co repo it is published in
+    pub fn download_url(&self, mirror_url: Option<&str>) -> String {
+        let base = mirror_url.map(str::trim).filter(|m| !m.is_empty())
+            .or(self.base_url)
+            .unwrap_or(DEFAULT_MIRROR_URL);
+        format!("{}/{}", base.trim_end_matches('/'), self.file_name())
+    }
+}
+
+/// Registry entry for a model name or alias
+pub fn lookup(name: &str) -> Option<&'static ModelSpec> {
+    let name = MODEL_ALIASES.iter().find(|(alias, _)| *alias == name).map_or(name, |(_, target)| target);
+    MODEL_REGISTRY.iter().find(|m| m.name == name)
+}
+
+/// Models to try for a request, best first: the model itself, then installed variants of the
+/// same size (quantized or English-only), then the usual smaller sizes and their installed variants
+pub fn fallback_chain(model: &str) -> Vec<String> {
+    let requested = lookup(model);
+    let smaller: &[&str] = match requested.map(|m| m.family) {
+        Some(family) => FAMILY_FALLBACKS.iter().find(|(f, _)| *f == family).map_or(&[], |(_, chain)| *chain),
+        None => &["base", "tiny"],
+    };
+
+    let mut chain = vec![model.to_string()];
+    for family in requested.map(|m| m.family).into_iter().chain(smaller.iter().copied()) {
+        // Closest to the full model first: multilingual before English-only, then by precision
+        let mut variants: Vec<&ModelSpec> = MODEL_REGISTRY.iter()
+            .filter(|m| m.family == family && requested.is_none_or(|r| r.name != m.name))
+            .collect();
+        variants.sort_by_key(|m| (m.languages == EN, quantization_rank(m.quantization)));
+        for variant in variants {
+            // The usual fallback sizes are always listed, variants only when they are installed
+            if smaller.contains(&variant.name) || find_installed(variant.name).is_some() {
+                chain.push(variant.name.to_string());
+            }
+        }
+    }
+    chain
+}
+
+fn quantization_rank(quantization: ModelQuantization) -> u8 {
+    match quantization {
+        F16 => 0,
+        Q8_0 => 1,
+        Q5_1 => 2,
+        Q5_0 => 3,
+    }
+}
+
+fn infer_languages(name: &str) -> ModelLanguages {
+    if name.contains(".en") { ModelLanguages::English } else { ModelLanguages::Multilingual }
+}
+
+fn infer_quantization(name: &str) -> ModelQuantization {
+    match name.rsplit('-').next() {
+        Some("q5_0") => ModelQuantization::Q5_0,
+        Some("q5_1") => ModelQuantization::Q5_1,
+        Some("q8_0") => ModelQuantization::Q8_0,
+        _ => ModelQuantization::F16,
+    }
+}
+
+fn get_custom_models_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::whisper::get_app_data_dir()?.join(CUSTOM_MODELS_FILE))
+}
+
+fn load_custom_models() -> CustomModelStore {
+    get_custom_models_path().ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_custom_models(store: &CustomModelStore) -> anyhow::Result<()> {
+    let path = get_custom_models_path()?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(store)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Where an installed model lives: a registered file, the models dir, or a system-wide location
+pub fn find_installed(name: &str) -> Option<PathBuf> {
+    if let Some(custom) = load_custom_models().models.into_iter().find(|m| m.name == name) {
+        let path = PathBuf::from(custom.path);
+        return path.is_file().then_some(path);
+    }
+    if name.contains(['/', '\\']) {
+        return None;
+    }
+    let file_name = lookup(name).map_or_else(|| format!("ggml-{}.bin", name), |m| m.file_name());
+
+    // The centralized models directory handles dev, production and platform-specific paths
+    let mut candidates = Vec::new();
+    if let Ok(models_dir) = crate::whisper::get_models_dir() {
+        candidates.push(models_dir.join(&file_name));
+    }
+    candidates.push(PathBuf::from("/opt/homebrew/share/whisper-models").join(&file_name));
+    candidates.push(PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".cache/whisper").join(&file_name));
+    candidates.into_iter().find(|p| p.is_file())
+}
+
+/// Check if a model exists
+pub fn check_model_exists(model_name: &str) -> anyhow::Result<bool> {
+    Ok(find_installed(model_name).is_some())
+}
+
+/// Reject files that aren't whisper.cpp ggml models before anyone tries to load them
+fn validate_ggml(path: &Path) -> anyhow::Result<u64> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Cannot read model file {}: {}", path.display(), e))?;
+    let size = file.metadata()?.len();
+    let mut magic = [0u8; 4];
+    if size < MIN_MODEL_BYTES || file.read_exact(&mut magic).is_err() || u32::from_le_bytes(magic) != GGML_MAGIC {
+        return Err(anyhow::anyhow!("{} is not a whisper.cpp ggml model", path.display()));
+    }
+    Ok(size)
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(PARTIAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// SHA-1 of everything in the file so far, read in chunks (models are gigabytes)
+fn sha1_file(path: &Path) -> std::io::Result<(sha1_smol::Sha1, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = sha1_smol::Sha1::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    let mut total = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        total += n as u64;
+    }
+    Ok((hasher, total))
+}
+
+fn modified_secs(meta: &std::fs::Metadata) -> u64 {
+    meta.modified().ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+fn load_checksums(models_dir: &Path) -> ModelChecksumIndex {
+    std::fs::read_to_string(models_dir.join(CHECKSUM_INDEX_FILE)).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_checksums(models_dir: &Path, index: &ModelChecksumIndex) -> anyhow::Result<()> {
+    let path = models_dir.join(CHECKSUM_INDEX_FILE);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(index)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+fn remember_checksum(index: &mut ModelChecksumIndex, path: &Path, sha1: String) {
+    if let Ok(meta) = std::fs::metadata(path) {
+        index.files.insert(path.to_string_lossy().to_string(), ModelChecksum { size: meta.len(), modified: modified_secs(&meta), sha1 });
+    }
+}
+
+/// SHA-1 of a model file, reusing the stored one while size and modification time are unchanged
+fn cached_sha1(index: &mut ModelChecksumIndex, path: &Path) -> anyhow::Result<String> {
+    let meta = std::fs::metadata(path)?;
+    let key = path.to_string_lossy().to_string();
+    if let Some(known) = index.files.get(&key).filter(|c| c.size == meta.len() && c.modified == modified_secs(&meta)) {
+        return Ok(known.sha1.clone());
+    }
+    let (hasher, _) = sha1_file(path)?;
+    let sha1 = hasher.digest().to_string();
+    remember_checksum(index, path, sha1.clone());
+    Ok(sha1)
+}
+
+/// The whole registry with what is installed, then registered files, then any other ggml
+/// file in the models dir. Known checksums are checked; the first listing hashes every
+/// installed file, after that only new or changed files are hashed.
+pub async fn list_models() -> anyhow::Result<Vec<ModelInfo>> {
+    tokio::task::spawn_blocking(|| {
+        let models_dir = crate::whisper::get_models_dir()?;
+        let mut index = load_checksums(&models_dir);
+        let mut models = Vec::new();
+
+        for spec in MODEL_REGISTRY {
+            let path = find_installed(spec.name);
+            let verified = match (&path, spec.sha1) {
+                (Some(path), Some(expected)) => cached_sha1(&mut index, path)? == expected,
+                _ => false,
+            };
+            let size = match &path {
+                Some(path) => std::fs::metadata(path)?.len(),
+                None => spec.size_mb as u64 * 1024 * 1024,
+            };
+            models.push(ModelInfo {
+                name: spec.name.to_string(),
+                size,
+                installed: path.is_some(),
+                path: path.map(|p| p.to_string_lossy().to_string()),
+                verified,
+                languages: spec.languages,
+                quantization: spec.quantization,
+                custom: false,
+            });
+        }
+
+        for custom in load_custom_models().models {
+            let path = PathBuf::from(&custom.path);
+            let size = std::fs::metadata(&path).map_or(0, |m| m.len());
+            models.push(ModelInfo {
+                languages: if custom.english_only { ModelLanguages::English } else { ModelLanguages::Multilingual },
+                quantization: infer_quantization(&custom.name),
+                name: custom.name,
+                size,
+                installed: path.is_file(),
+                path: Some(custom.path),
+                verified: false,
+                custom: true,
+            });
+        }
+
+        for entry in std::fs::read_dir(&models_dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(name) = file_name.strip_prefix("ggml-").and_then(|n| n.strip_suffix(".bin")) else { continue };
+            if lookup(name).is_some() || models.iter().any(|m| m.name == name) || !entry.file_type()?.is_file() {
+                continue;
+            }
+            models.push(ModelInfo {
+                name: name.to_string(),
+                size: entry.metadata()?.len(),
+                path: Some(entry.path().to_string_lossy().to_string()),
+                installed: true,
+                verified: false,
+                languages: infer_languages(name),
+                quantization: infer_quantization(name),
+                custom: false,
+            });
+        }
+
+        index.files.retain(|path, _| Path::new(path).exists());
+        // The models dir can be read-only (bundled models); checksums are then just recomputed
+        let _ = save_checksums(&models_dir, &index);
+        Ok(models)
+    }).await?
+}
+
+/// Use a ggml model file from anywhere on disk under a name of the user's choosing
+pub async fn register_model(p: RegisterModelParams) -> anyhow::Result<ModelInfo> {
+    let name = p.name.trim().to_string();
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(anyhow::anyhow!("Invalid model name: {:?}", p.name));
+    }
+    if lookup(&name).is_some() {
+        return Err(anyhow::anyhow!("{} is a built-in model name, please choose another", name));
+    }
+    let path = std::fs::canonicalize(&p.path)
+        .map_err(|e| anyhow::anyhow!("Cannot read model file {}: {}", p.path, e))?;
+    let size = validate_ggml(&path)?;
+
+    let custom = CustomModel { name: name.clone(), path: path.to_string_lossy().to_string(), english_only: p.english_only };
+    let mut store = load_custom_models();
+    store.models.retain(|m| m.name != name);
+    store.models.push(custom.clone());
+    save_custom_models(&store)?;
+
+    Ok(ModelInfo {
+        name,
+        size,
+        path: Some(custom.path),
+        installed: true,
+        verified: false,
+        languages: if p.english_only { ModelLanguages::English } else { ModelLanguages::Multilingual },
+        quantization: infer_quantization(&p.name),
+        custom: true,
+    })
+}
+
+/// Copy a ggml model file into the models dir, for machines that can't download.
+/// A file that matches a registry checksum is installed as that model, whatever it's called.
+pub async fn import_model(id: &str, p: ImportModelParams, mut emit: impl FnMut(RpcEvent)) -> anyhow::Result<ModelInfo> {
+    let source = PathBuf::from(&p.path);
+    let size = validate_ggml(&source)?;
+    let models_dir = crate::whisper::get_models_dir()
+        .map_err(|e| anyhow::anyhow!("Cannot access models directory: {}. Please check app permissions.", e))?;
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Importing {} ({:.1} MB) into {}", source.display(), size as f64 / 1024.0 / 1024.0, models_dir.display())
+    });
+
+    // Copy and hash in one pass, into a temp file next to the destination
+    let part_path = models_dir.join(format!("import-{}.bin{}", id, PARTIAL_SUFFIX));
+    let copy = {
+        let (source, part_path) = (source.clone(), part_path.clone());
+        tokio::task::spawn_blocking(move || -> std::io::Result<String> {
+            let mut input = std::fs::File::open(&source)?;
+            let mut output = std::fs::File::create(&part_path)?;
+            let mut hasher = sha1_smol::Sha1::new();
+            let mut buf = vec![0u8; 1024 * 1024];
+            loop {
+                let n = input.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+                output.write_all(&buf[..n])?;
+            }
+            output.sync_all()?;
+            Ok(hasher.digest().to_string())
+        }).await?
+    };
+    let sha1 = match copy {
+        Ok(sha1) => sha1,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(anyhow::anyhow!("Cannot copy {} into the models directory: {}", source.display(), e));
+        }
+    };
+
+    let matched = MODEL_REGISTRY.iter().find(|m| m.sha1 == Some(sha1.as_str()));
+    let requested = p.name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let name = match (matched, requested) {
+        (Some(spec), _) => spec.name.to_string(),
+        (None, Some(name)) => name.to_string(),
+        (None, None) => {
+            let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            stem.strip_prefix("ggml-").unwrap_or(&stem).to_string()
+        }
+    };
+    let spec = lookup(&name);
+    let error = if name.is_empty() || name.contains(['/', '\\']) {
+        Some(format!("Invalid model name: {:?}", name))
+    } else if spec.is_some_and(|s| s.sha1.is_some_and(|expected| expected != sha1)) {
+        Some(format!("{} does not match the checksum of the {} model; import it under another name", source.display(), name))
+    } else {
+        None
+    };
+    if let Some(error) = error {
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err(anyhow::anyhow!(error));
+    }
+
+    let file_name = spec.map_or_else(|| format!("ggml-{}.bin", name), |s| s.file_name());
+    let output_path = models_dir.join(&file_name);
+    tokio::fs::rename(&part_path, &output_path).await?;
+
+    let mut index = load_checksums(&models_dir);
+    remember_checksum(&mut index, &output_path, sha1);
+    let _ = save_checksums(&models_dir, &index);
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Imported model {} to {}", name, output_path.display())
+    });
+
+    Ok(ModelInfo {
+        size,
+        path: Some(output_path.to_string_lossy().to_string()),
+        installed: true,
+        verified: spec.is_some_and(|s| s.sha1.is_some()),
+        languages: spec.map_or_else(|| infer_languages(&name), |s| s.languages),
+        quantization: spec.map_or_else(|| infer_quantization(&name), |s| s.quantization),
+        custom: false,
+        name,
+    })
+}
+
+/// Delete a downloaded model, together with any partial download of it.
+/// Registered files belong to the user: they are only forgotten, never deleted.
+pub async fn delete_model(p: DeleteModelParams) -> anyhow::Result<DeleteModelResult> {
+    let mut store = load_custom_models();
+    if store.models.iter().any(|m| m.name == p.model) {
+        store.models.retain(|m| m.name != p.model);
+        save_custom_models(&store)?;
+        return Ok(DeleteModelResult { model: p.model, freed: 0 });
+    }
+
+    if p.model.contains(['/', '\\']) {
+        return Err(anyhow::anyhow!("Unknown model: {}", p.model));
+    }
+    let file_name = lookup(&p.model).map_or_else(|| format!("ggml-{}.bin", p.model), |m| m.file_name());
+    let models_dir = crate::whisper::get_models_dir()?;
+    let path = models_dir.join(&file_name);
+
+    let mut freed = 0;
+    for file in [path.clone(), partial_path(&path)] {
+        if let Ok(meta) = tokio::fs::metadata(&file).await {
+            tokio::fs::remove_file(&file).await
+                .map_err(|e| anyhow::anyhow!("Cannot delete {}: {}", file.display(), e))?;
+            freed += meta.len();
+        }
+    }
+    if freed == 0 {
+        return Err(anyhow::anyhow!("Model {} is not installed", p.model));
+    }
+
+    let mut index = load_checksums(&models_dir);
+    if index.files.remove(path.to_string_lossy().as_ref()).is_some() {
+        let _ = save_checksums(&models_dir, &index);
+    }
+    Ok(DeleteModelResult { model: p.model, freed })
+}
+
+/// Public RPC method to download a whisper model with progress reporting.
+/// The download goes to a `.part` file that is resumed with an HTTP Range request if it was
+/// interrupted, and only takes the model's real name once its checksum matches the registry.
+pub async fn download_model_rpc(
+    id: &str,
+    params: DownloadModelParams,
+    mut emit: impl FnMut(RpcEvent)
+) -> anyhow::Result<DownloadModelResult> {
+    let spec = lookup(&params.model)
+        .ok_or_else(|| anyhow::anyhow!("Unknown model: {}. Supported: {}", params.model,
+            MODEL_REGISTRY.iter().map(|m| m.name).collect::<Vec<_>>().join(", ")))?;
+    let model_filename = spec.file_name();
+
+    let url = spec.download_url(params.mirror_url.as_deref());
+    let models_dir = crate::whisper::get_models_dir()
+        .map_err(|e| anyhow::anyhow!("Cannot access models directory: {}. Please check app permissions.", e))?;
+    let output_path = models_dir.join(&model_filename);
+    let part_path = partial_path(&output_path);
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Models will be saved to: {}", models_dir.display())
+    });
+
+    // Hash what an earlier attempt already downloaded, so only the rest has to be hashed as it arrives
+    let (mut hasher, mut downloaded) = if part_path.exists() {
+        let part = part_path.clone();
+        tokio::task::spawn_blocking(move || sha1_file(&part)).await??
+    } else {
+        (sha1_smol::Sha1::new(), 0)
+    };
+
+    let mut client = reqwest::Client::builder();
+    if let Some(proxy) = params.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        client = client.proxy(reqwest::Proxy::all(proxy)
+            .map_err(|e| anyhow::anyhow!("Invalid proxy {}: {}", proxy, e))?);
+    }
+    let client = client.build()?;
+    let mut request = client.get(&url);
+    if downloaded > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", downloaded));
+    }
+    let response = request.send().await?;
+
+    let resumed = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let already_complete = downloaded > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE;
+    if !response.status().is_success() && !already_complete {
+        return Err(anyhow::anyhow!("Failed to download model: HTTP {}", response.status()));
+    }
+
+    if !already_complete {
+        if !resumed && downloaded > 0 {
+            // The server ignored the range; start over
+            hasher = sha1_smol::Sha1::new();
+            downloaded = 0;
+        }
+        let total_size = response.content_length().map_or(0, |len| len + downloaded);
+
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: if resumed {
+                format!("Resuming download of {} at {:.1} of {:.1} MB", model_filename,
+                    downloaded as f64 / 1024.0 / 1024.0, total_size as f64 / 1024.0 / 1024.0)
+            } else {
+                format!("Downloading {} ({:.1} MB) from {}...", model_filename, total_size as f64 / 1024.0 / 1024.0, url)
+            }
+        });
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&part_path).await
+            .map_err(|e| anyhow::anyhow!("Cannot create model file at {}: {}. Check app permissions in System Settings > Privacy & Security.", part_path.display(), e))?;
+        let mut stream = response.bytes_stream();
+        let mut last_progress = 0u64;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            hasher.update(&chunk);
+            downloaded += chunk.len() as u64;
+
+            if downloaded - last_progress >= PROGRESS_STEP_BYTES || downloaded == total_size {
+                last_progress = downloaded;
+                let progress = if total_size > 0 {
+                    (downloaded as f64 / total_size as f64) as f32
+                } else {
+                    0.0_f32
+                };
+                emit(RpcEvent::Progress {
+                    id: id.into(),
+                    status: format!("Downloading {}...", params.model),
+                    progress
+                });
+            }
+        }
+
+        file.flush().await?;
+        file.sync_all().await?;
+    }
+
+    // An error page or a truncated response must never be installed as a model
+    let part = part_path.clone();
+    if let Err(e) = tokio::task::spawn_blocking(move || validate_ggml(&part)).await? {
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return Err(anyhow::anyhow!("Download of {} from {} failed: {}", model_filename, url, e));
+    }
+
+    let sha1 = hasher.digest().to_string();
+    match spec.sha1 {
+        Some(expected) if sha1 != expected => {
+            // A corrupt partial download can't be resumed into a good one
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return Err(anyhow::anyhow!("Downloaded {} is corrupt (SHA-1 {} instead of {}), please download it again",
+                model_filename, sha1, expected));
+        }
+        Some(_) => {}
+        None => emit(RpcEvent::Log {
+            id: id.into(),
+            message: format!("No checksum is known for {}, skipping verification", model_filename)
+        }),
+    }
+    tokio::fs::rename(&part_path, &output_path).await?;
+
+    let mut index = load_checksums(&models_dir);
+    remember_checksum(&mut index, &output_path, sha1);
+    let _ = save_checksums(&models_dir, &index);
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Successfully downloaded {} model to {}", params.model, output_path.display())
+    });
+
+    Ok(DownloadModelResult {
+        model: params.model,
+        path: output_path.to_string_lossy().to_string(),
+        size: downloaded
+    })
+}
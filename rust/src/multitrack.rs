@@ -0,0 +1,351 @@
+use crate::audio::{self, SpeakerSource};
+use crate::rpc::RpcEvent;
+use crate::types::{CaptionSegment, LowConfidenceWord, MultitrackSource, TranscribeSegmentsParams, TranscribeSegmentsResult};
+use crate::video::ProbeResult;
+use std::path::PathBuf;
+
+// ---- Overlap resolution tuning ----
+const BLEED_SILENT_FRACTION: f64 = 0.6; // a caption mostly inside its own mic's silence is crosstalk
+const MIN_CAPTION_MS: u64 = 200;        // never trim a caption shorter than this
+const BACKCHANNEL_MAX_WORDS: usize = 2; // "yeah", "mm-hmm" said under someone else's sentence
+const PHRASE_GAP_MS: u64 = 700;         // word captions closer than this belong to one phrase
+
+/// One source per speaker, in speaker order
+pub fn speaker_sources(probe: &ProbeResult, mode: MultitrackSource) -> anyhow::Result<Vec<SpeakerSource>> {
+    match mode {
+        MultitrackSource::Streams => {
+            let streams = probe.audio_tracks.len() as u32;
+            if streams < 2 {
+                return Err(anyhow::anyhow!("Input has {} audio stream(s); per-speaker transcription needs one stream per speaker", streams));
+            }
+            Ok((0..streams).map(SpeakerSource::Stream).collect())
+        }
+        MultitrackSource::Channels => {
+            let channels = probe.audio_tracks.first().map_or(0, |t| t.channels);
+            if channels < 2 {
+                return Err(anyhow::anyhow!("First audio stream has {} channel(s); per-speaker transcription needs one channel per speaker", channels));
+            }
+            Ok((0..channels).map(SpeakerSource::Channel).collect())
+        }
+    }
+}
+
+/// Share of [start, end] that falls inside the given silences
+fn silent_fraction(start_ms: u64, end_ms: u64, silences: &[(f64, f64)]) -> f64 {
+    let (start, end) = (start_ms as f64 / 1000.0, end_ms as f64 / 1000.0);
+    if end <= start {
+        return 0.0;
+    }
+    let silent: f64 = silences.iter()
+        .map(|(s, e)| (e.min(end) - s.max(start)).max(0.0))
+        .sum();
+    silent / (end - start)
+}
+
+/// Make overlapping captions from different speakers readable one after another.
+/// Short interjections under someone else's caption are dropped; otherwise an
+/// interruption cuts the earlier caption short, or waits for it when both start together.
+fn resolve_overlaps(mut segments: Vec<CaptionSegment>) -> Vec<CaptionSegment> {
+    let kept = resolve_in_place(&mut segments);
+    let mut out: Vec<CaptionSegment> = segments.into_iter()
+        .zip(kept)
+        .filter_map(|(seg, kept)| kept.then_some(seg))
+        .collect();
+    out.sort_by_key(|s| s.start_ms);
+    out
+}
+
+/// Overlap rules applied to start-ordered captions in place; returns which ones are kept
+fn resolve_in_place(segments: &mut [CaptionSegment]) -> Vec<bool> {
+    let mut kept = vec![false; segments.len()];
+
+    'segments: for i in 0..segments.len() {
+        let (done, rest) = segments.split_at_mut(i);
+        let seg = &mut rest[0];
+        // A delayed caption can end up after later ones, so check every other speaker's
+        // caption that still overlaps, latest-ending first, not just the previous one
+        while let Some(p) = done.iter()
+            .enumerate()
+            .filter(|(j, o)| kept[*j] && o.speaker != seg.speaker && o.end_ms > seg.start_ms && o.start_ms < seg.end_ms)
+            .max_by_key(|(_, o)| o.end_ms)
+            .map(|(j, _)| j)
+        {
+            let prev = &mut done[p];
+            let backchannel = seg.end_ms <= prev.end_ms && seg.text.split_whitespace().count() <= BACKCHANNEL_MAX_WORDS;
+            if backchannel {
+                continue 'segments;
+            }
+            if seg.start_ms >= prev.start_ms + MIN_CAPTION_MS {
+                prev.end_ms = seg.start_ms;
+                for word in &mut prev.words {
+                    word.end_ms = word.end_ms.min(prev.end_ms);
+                    word.start_ms = word.start_ms.min(word.end_ms);
+                }
+            } else {
+                let length = seg.end_ms - seg.start_ms;
+                seg.start_ms = prev.end_ms;
+                seg.end_ms = seg.end_ms.max(seg.start_ms + length.min(MIN_CAPTION_MS));
+            }
+        }
+        for word in &mut seg.words {
+            word.start_ms = word.start_ms.max(seg.start_ms);
+            word.end_ms = word.end_ms.max(word.start_ms);
+        }
+        kept[i] = true;
+    }
+
+    kept
+}
+
+/// Word-by-word captions are grouped into each speaker's phrases before resolving, so the
+/// backchannel rule and truncation see whole utterances rather than single words. Kept
+/// phrases are split back into words, retimed to fit the phrase's resolved span.
+fn resolve_word_overlaps(words: Vec<CaptionSegment>) -> Vec<CaptionSegment> {
+    let mut phrases: Vec<CaptionSegment> = Vec::new();
+    let mut members: Vec<Vec<CaptionSegment>> = Vec::new();
+    let mut open: Vec<(Option<u32>, usize)> = Vec::new(); // speaker -> phrase still being built
+
+    for word in words {
+        let current = open.iter().find(|(speaker, _)| *speaker == word.speaker).map(|(_, i)| *i);
+        match current {
+            Some(i) if word.start_ms <= phrases[i].end_ms + PHRASE_GAP_MS => {
+                let phrase = &mut phrases[i];
+                phrase.end_ms = phrase.end_ms.max(word.end_ms);
+                phrase.text.push(' ');
+                phrase.text.push_str(word.text.trim());
+                members[i].push(word);
+            }
+            _ => {
+                open.retain(|(speaker, _)| *speaker != word.speaker);
+                open.push((word.speaker, phrases.len()));
+                phrases.push(CaptionSegment { text: word.text.trim().to_string(), words: Vec::new(), ..word.clone() });
+                members.push(vec![word]);
+            }
+        }
+    }
+
+    let original: Vec<(u64, u64)> = phrases.iter().map(|p| (p.start_ms, p.end_ms)).collect();
+    let kept = resolve_in_place(&mut phrases);
+
+    let mut out = Vec::new();
+    for (((phrase, words), (from_start, from_end)), kept) in phrases.iter().zip(members).zip(original).zip(kept) {
+        if !kept {
+            continue;
+        }
+        let retime = |t: u64| -> u64 {
+            if from_end <= from_start {
+                return phrase.start_ms;
+            }
+            let offset = (t.clamp(from_start, from_end) - from_start) as f64 / (from_end - from_start) as f64;
+            phrase.start_ms + (offset * (phrase.end_ms - phrase.start_ms) as f64).round() as u64
+        };
+        for mut word in words {
+            word.start_ms = retime(word.start_ms);
+            word.end_ms = retime(word.end_ms);
+            for span in &mut word.words {
+                span.start_ms = retime(span.start_ms);
+                span.end_ms = retime(span.end_ms);
+            }
+            out.push(word);
+        }
+    }
+
+    out.sort_by_key(|s| s.start_ms);
+    out
+}
+
+/// Transcribe each speaker's stream or channel on its own and merge everything into one
+/// timeline, labelled with the speaker number. No diarization model is involved: the
+/// microphone a caption came from is who said it.
+pub async fn transcribe_multitrack(
+    id: &str,
+    input: &str,
+    probe: &ProbeResult,
+    mode: MultitrackSource,
+    params: &TranscribeSegmentsParams,
+    temp_dir: &PathBuf,
+    mut emit: impl FnMut(RpcEvent) + Send,
+) -> anyhow::Result<TranscribeSegmentsResult> {
+    let sources = speaker_sources(probe, mode)?;
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Transcribing {} speakers separately ({:?})", sources.len(), mode)
+    });
+
+    let mut segments = Vec::new();
+    let mut low_confidence_words = Vec::new();
+    let mut language_votes: Vec<(String, usize)> = Vec::new();
+    let mut duration: Option<f64> = None;
+
+    for (speaker, source) in sources.into_iter().enumerate() {
+        let speaker = speaker as u32;
+        let track_path = temp_dir.join(format!("speaker_{}_{}.wav", id, speaker)).to_string_lossy().to_string();
+        audio::extract_speaker_audio(input, source, std::path::Path::new(&track_path)).await?;
+
+        // Each mic also picks up the other speakers, only quieter; their words land in this track's silences
+        let silences = match crate::chunking::detect_silences(&track_path).await {
+            Ok(silences) => silences,
+            Err(e) => {
+                emit(RpcEvent::Log { id: id.into(), message: format!("Silence detection failed for speaker {}: {}", speaker, e) });
+                Vec::new()
+            }
+        };
+
+        let track_params = TranscribeSegmentsParams {
+            audio: track_path.clone(),
+            diarize: false,
+            ..params.clone()
+        };
+        let result = crate::whisper::transcribe_segments_with_temp(id, track_params, Some(temp_dir), &mut emit).await;
+        let _ = tokio::fs::remove_file(&track_path).await;
+        let result = match result {
+            Ok(result) => result,
+            // A mic that stayed silent the whole time is not an error
+            Err(e) if crate::whisper::is_no_speech(&e) => {
+                emit(RpcEvent::Log { id: id.into(), message: format!("Speaker {} said nothing", speaker) });
+                continue;
+            }
+            // Skipping the track would silently drop a speaker from the merged captions
+            Err(e) => return Err(e.context(format!("Transcription failed for speaker {}", speaker))),
+        };
+
+        duration = match (duration, result.duration) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        low_confidence_words.extend(result.low_confidence_words.into_iter()
+            .filter(|w| silent_fraction(w.start_ms, w.end_ms, &silences) < BLEED_SILENT_FRACTION));
+        let mut kept = 0usize;
+        let mut crosstalk = 0usize;
+        for mut seg in result.segments {
+            if silent_fraction(seg.start_ms, seg.end_ms, &silences) >= BLEED_SILENT_FRACTION {
+                crosstalk += 1;
+                continue;
+            }
+            seg.speaker = Some(speaker);
+            for word in &mut seg.words {
+                word.speaker = Some(speaker);
+            }
+            segments.push(seg);
+            kept += 1;
+        }
+        if let Some(lang) = result.language {
+            match language_votes.iter_mut().find(|(l, _)| *l == lang) {
+                Some((_, n)) => *n += kept,
+                None => language_votes.push((lang, kept)),
+            }
+        }
+
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: format!("Speaker {}: kept {} captions, dropped {} picked up from other mics", speaker, kept, crosstalk)
+        });
+    }
+
+    if segments.is_empty() {
+        return Err(anyhow::anyhow!("No speech was transcribed from any speaker track"));
+    }
+
+    segments.sort_by_key(|s| s.start_ms);
+    let segments = if params.split_by_words { resolve_word_overlaps(segments) } else { resolve_overlaps(segments) };
+    let full_text = segments.iter().map(|s| s.text.trim()).collect::<Vec<_>>().join(" ");
+    low_confidence_words.sort_by_key(|w: &LowConfidenceWord| w.start_ms);
+    let language = language_votes.into_iter().max_by_key(|(_, n)| *n).map(|(l, _)| l);
+
+    let json_path = temp_dir.join(format!("transcription_{}.json", id));
+    let json_data = serde_json::json!({
+        "segments": segments,
+        "lowConfidenceWords": low_confidence_words,
+        "fullText": full_text,
+        "duration": duration,
+        "splitByWords": params.split_by_words,
+        "multitrack": mode,
+        "language": language,
+    });
+    tokio::fs::write(&json_path, serde_json::to_string_pretty(&json_data)?).await?;
+
+    Ok(TranscribeSegmentsResult {
+        segments,
+        full_text,
+        duration,
+        json_file: json_path.to_string_lossy().to_string(),
+        language,
+        low_confidence_words,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(speaker: u32, start_ms: u64, end_ms: u64, text: &str) -> CaptionSegment {
+        CaptionSegment {
+            start_ms, end_ms, text: text.into(), words: Vec::new(),
+            language: None, speaker: Some(speaker), confidence: None, hidden: false,
+        }
+    }
+
+    fn spans(segments: &[CaptionSegment]) -> Vec<(u32, u64, u64)> {
+        segments.iter().map(|s| (s.speaker.unwrap(), s.start_ms, s.end_ms)).collect()
+    }
+
+    #[test]
+    fn interruption_cuts_the_earlier_caption() {
+        let out = resolve_overlaps(vec![seg(0, 0, 3000, "a long first sentence"), seg(1, 2000, 4000, "and a reply to it")]);
+        assert_eq!(spans(&out), [(0, 0, 2000), (1, 2000, 4000)]);
+    }
+
+    #[test]
+    fn simultaneous_start_waits_and_backchannels_drop() {
+        let out = resolve_overlaps(vec![
+            seg(0, 0, 3000, "a long first sentence"),
+            seg(1, 100, 3500, "starting at the same time"),
+            seg(2, 1000, 1500, "yeah"),
+        ]);
+        assert_eq!(spans(&out), [(0, 0, 3000), (1, 3000, 3500)]);
+    }
+
+    #[test]
+    fn word_captions_are_resolved_as_phrases() {
+        let words = [
+            (0, 0, 400, "a"), (0, 400, 900, "long"), (0, 900, 1500, "first"), (0, 1500, 3000, "sentence"),
+            (1, 1000, 1400, "yeah"),
+            (2, 2000, 2500, "and"), (2, 2500, 3000, "a"), (2, 3000, 4000, "reply"),
+        ];
+        let mut segments: Vec<CaptionSegment> = words.iter().map(|&(sp, s, e, t)| seg(sp, s, e, t)).collect();
+        segments.sort_by_key(|s| s.start_ms);
+        let out = resolve_word_overlaps(segments);
+
+        // Every real word survives; only the backchannel under speaker 0's sentence is dropped
+        let texts: Vec<&str> = out.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["a", "long", "first", "sentence", "and", "a", "reply"]);
+        // Speaker 0's phrase is cut where speaker 2 starts, its words squeezed to fit
+        assert_eq!(spans(&out)[..4], [(0, 0, 267), (0, 267, 600), (0, 600, 1000), (0, 1000, 2000)]);
+        assert_eq!(spans(&out)[4..], [(2, 2000, 2500), (2, 2500, 3000), (2, 3000, 4000)]);
+    }
+
+    #[test]
+    fn overlap_with_an_earlier_longer_caption_is_resolved() {
+        // The second caption of speaker 0 ends first; speaker 1 still overlaps the first one
+        let out = resolve_overlaps(vec![
+            seg(0, 0, 5000, "a very long monologue goes on"),
+            seg(0, 1000, 2000, "overlapping own line"),
+            seg(1, 3000, 4000, "three words here"),
+        ]);
+        assert_eq!(spans(&out), [(0, 0, 3000), (0, 1000, 2000), (1, 3000, 4000)]);
+    }
+
+    #[test]
+    fn delayed_captions_never_overlap_other_speakers() {
+        let out = resolve_overlaps(vec![
+            seg(0, 0, 5000, "a very long monologue goes on"),
+            seg(1, 100, 1000, "an interruption of five words"),
+            seg(2, 150, 6000, "a third voice joins right in"),
+        ]);
+        for (i, a) in out.iter().enumerate() {
+            for b in &out[i + 1..] {
+                assert!(a.speaker == b.speaker || a.end_ms <= b.start_ms || b.end_ms <= a.start_ms, "{:?}", spans(&out));
+            }
+        }
+    }
+}
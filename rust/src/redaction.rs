@@ -0,0 +1,256 @@
+use crate::filtergraph::{Filter, FilterChain};
+use crate::types::{CaptionSegment, RedactionAudio, RedactionMask, RedactionParams, TranscribeSegmentsResult};
+use regex::Regex;
+use std::sync::LazyLock;
+
+// Built-in list, matched as whole words plus common endings ("fucking", "shits")
+const PROFANITY_WORDS: &[&str] = &[
+    "fuck", "motherfuck", "shit", "bullshit", "bitch", "bastard", "asshole", "ass", "dick",
+    "cock", "cunt", "pussy", "piss", "damn", "goddamn", "crap", "slut", "whore", "wanker",
+    "bollocks", "twat", "prick", "douche",
+];
+const WORD_ENDINGS: &str = "(?:s|es|ed|er|ers|ing|in|y|head|heads)?";
+
+// ---- Audio tuning ----
+const BLEEP_HZ: u32 = 1000;
+const BLEEP_GAIN: f64 = 0.25;  // a full-scale sine is painfully loud
+const AUDIO_PAD_MS: u64 = 60;  // word timings are approximate; cover the edges too
+
+static EMAIL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b[\w.+-]+@[\w-]+(?:\.[\w-]+)*\.[a-z]{2,}\b").unwrap()
+});
+// Digit runs with phone/card separators; the digit count decides whether it's personal
+static NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\+?\(?\d[\d ().-]{6,}\d").unwrap()
+});
+const MIN_PII_DIGITS: usize = 9;   // shortest phone numbers without country code
+const MAX_PII_DIGITS: usize = 19;  // longest card numbers
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Profanity,
+    Pii,
+}
+
+// A redacted byte range in some text
+#[derive(Debug, Clone, Copy)]
+struct Hit {
+    start: usize,
+    end: usize,
+    kind: Kind,
+}
+
+// Where a piece of caption text lives: a timed word, or a segment without word timings
+#[derive(Debug, Clone, Copy)]
+enum Unit {
+    Word(usize, usize),
+    Segment(usize),
+}
+
+struct Piece {
+    unit: Unit,
+    start: usize, // byte range in the joined caption text
+    end: usize,
+    start_ms: u64,
+    end_ms: u64,
+}
+
+pub struct Redactor {
+    profanity: Option<Regex>,
+    pii: bool,
+    mask: RedactionMask,
+}
+
+impl Redactor {
+    pub fn new(params: &RedactionParams) -> anyhow::Result<Self> {
+        let profanity = if params.profanity {
+            let mut words: Vec<String> = match &params.profanity_words {
+                Some(list) => list.iter().map(|w| w.trim().to_lowercase()).filter(|w| !w.is_empty()).collect(),
+                None => PROFANITY_WORDS.iter().map(|w| w.to_string()).collect(),
+            };
+            // Longest first so "motherfuck" wins over "fuck"
+            words.sort_by_key(|w| std::cmp::Reverse(w.len()));
+            if words.is_empty() {
+                None
+            } else {
+                let alternation = words.iter().map(|w| regex::escape(w)).collect::<Vec<_>>().join("|");
+                Some(Regex::new(&format!(r"(?i)\b(?:{}){}\b", alternation, WORD_ENDINGS))?)
+            }
+        } else {
+            None
+        };
+        Ok(Self { profanity, pii: params.pii, mask: params.mask })
+    }
+
+    /// Sorted, non-overlapping ranges to redact
+    fn find(&self, text: &str) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        if let Some(re) = &self.profanity {
+            hits.extend(re.find_iter(text).map(|m| Hit { start: m.start(), end: m.end(), kind: Kind::Profanity }));
+        }
+        if self.pii {
+            hits.extend(EMAIL_RE.find_iter(text).map(|m| Hit { start: m.start(), end: m.end(), kind: Kind::Pii }));
+            hits.extend(NUMBER_RE.find_iter(text)
+                .filter(|m| (MIN_PII_DIGITS..=MAX_PII_DIGITS).contains(&m.as_str().chars().filter(char::is_ascii_digit).count()))
+                .map(|m| Hit { start: m.start(), end: m.end(), kind: Kind::Pii }));
+        }
+
+        hits.sort_by_key(|h| h.start);
+        let mut merged: Vec<Hit> = Vec::with_capacity(hits.len());
+        for hit in hits {
+            match merged.last_mut() {
+                Some(last) if hit.start < last.end => {
+                    last.end = last.end.max(hit.end);
+                    if hit.kind == Kind::Pii { last.kind = Kind::Pii; }
+                }
+                _ => merged.push(hit),
+            }
+        }
+        merged
+    }
+
+    /// Mask parts of `text`. Each range is (start, end, kind, starts_here): a hit can continue
+    /// from a previous piece of caption text, which decides where the label and first letter go.
+    fn mask(&self, text: &str, ranges: &[(usize, usize, Kind, bool)]) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut pos = 0;
+        for &(start, end, kind, starts_here) in ranges {
+            out.push_str(&text[pos..start]);
+            match self.mask {
+                RedactionMask::Label => {
+                    if starts_here { out.push_str("[redacted]"); }
+                }
+                RedactionMask::Partial => {
+                    // Swearing stays readable from its first letter; personal data doesn't
+                    let mut keep_first = starts_here && kind == Kind::Profanity;
+                    for c in text[start..end].chars() {
+                        if !c.is_alphanumeric() {
+                            out.push(c);
+                        } else if keep_first {
+                            out.push(c);
+                            keep_first = false;
+                        } else {
+                            out.push('*');
+                        }
+                    }
+                }
+            }
+            pos = end;
+        }
+        out.push_str(&text[pos..]);
+        out
+    }
+
+    /// Redact a standalone piece of text
+    pub fn redact_text(&self, text: &str) -> String {
+        let ranges: Vec<_> = self.find(text).into_iter().map(|h| (h.start, h.end, h.kind, true)).collect();
+        if ranges.is_empty() { text.to_string() } else { self.mask(text, &ranges) }
+    }
+
+    /// Redact caption text in place and return the time ranges of the redacted speech.
+    /// Captions are matched as one running text, so a phone number transcribed word by
+    /// word is still caught. Words emptied by a label are dropped.
+    pub fn redact_segments(&self, segments: &mut Vec<CaptionSegment>) -> Vec<(u64, u64)> {
+        let mut joined = String::new();
+        let mut pieces = Vec::new();
+        for (si, seg) in segments.iter().enumerate() {
+            if seg.words.is_empty() {
+                push_piece(&mut joined, &mut pieces, Unit::Segment(si), &seg.text, seg.start_ms, seg.end_ms);
+            } else {
+                for (wi, w) in seg.words.iter().enumerate() {
+                    push_piece(&mut joined, &mut pieces, Unit::Word(si, wi), &w.text, w.start_ms, w.end_ms);
+                }
+            }
+        }
+
+        let hits = self.find(&joined);
+        if hits.is_empty() {
+            return Vec::new();
+        }
+
+        let mut times = Vec::with_capacity(hits.len());
+        let mut changed = vec![false; segments.len()];
+        for piece in &pieces {
+            let ranges: Vec<_> = hits.iter()
+                .filter(|h| h.start < piece.end && h.end > piece.start)
+                .map(|h| (h.start.max(piece.start) - piece.start, h.end.min(piece.end) - piece.start, h.kind, h.start >= piece.start))
+                .collect();
+            if ranges.is_empty() { continue; }
+
+            let text = match piece.unit {
+                Unit::Word(si, wi) => &mut segments[si].words[wi].text,
+                Unit::Segment(si) => &mut segments[si].text,
+            };
+            let single_word = !text.trim().contains(char::is_whitespace);
+            let length = text.len().max(1) as u64;
+            let duration = piece.end_ms.saturating_sub(piece.start_ms);
+            for &(start, end, _, _) in &ranges {
+                // Inside a sentence-long caption, estimate where the words fall
+                let (start_ms, end_ms) = if single_word {
+                    (piece.start_ms, piece.end_ms)
+                } else {
+                    (piece.start_ms + duration * start as u64 / length, piece.start_ms + duration * end as u64 / length)
+                };
+                times.push((start_ms.saturating_sub(AUDIO_PAD_MS), end_ms + AUDIO_PAD_MS));
+            }
+            *text = self.mask(text, &ranges);
+            let (Unit::Word(si, _) | Unit::Segment(si)) = piece.unit;
+            changed[si] = true;
+        }
+
+        for (seg, changed) in segments.iter_mut().zip(changed) {
+            if !changed || seg.words.is_empty() { continue; }
+            seg.words.retain(|w| !w.text.trim().is_empty());
+            seg.text = seg.words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+        }
+        segments.retain(|s| !s.text.trim().is_empty());
+
+        times.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(times.len());
+        for (start, end) in times {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+
+    /// Redact captions, full text and the review list of a transcription
+    pub fn redact_transcription(&self, transcription: &mut TranscribeSegmentsResult) -> Vec<(u64, u64)> {
+        let times = self.redact_segments(&mut transcription.segments);
+        transcription.full_text = self.redact_text(&transcription.full_text);
+        for word in &mut transcription.low_confidence_words {
+            word.text = self.redact_text(&word.text);
+        }
+        times
+    }
+}
+
+fn push_piece(joined: &mut String, pieces: &mut Vec<Piece>, unit: Unit, text: &str, start_ms: u64, end_ms: u64) {
+    if !joined.is_empty() { joined.push(' '); }
+    let start = joined.len();
+    joined.push_str(text);
+    pieces.push(Piece { unit, start, end: joined.len(), start_ms, end_ms });
+}
+
+/// `-af` filter that bleeps or mutes the given time ranges, if there is anything to do
+pub fn audio_filter(ranges: &[(u64, u64)], audio: RedactionAudio) -> Option<String> {
+    if ranges.is_empty() {
+        return None;
+    }
+    let during = ranges.iter()
+        .map(|(s, e)| format!("between(t,{:.3},{:.3})", *s as f64 / 1000.0, *e as f64 / 1000.0))
+        .collect::<Vec<_>>()
+        .join("+");
+
+    let filter = match audio {
+        RedactionAudio::None => return None,
+        RedactionAudio::Mute => Filter::new("volume").opt("volume", 0).opt("enable", during),
+        // One expression covers every channel; outside the ranges each sample passes through
+        RedactionAudio::Bleep => Filter::new("aeval")
+            .opt("exprs", format!("if({},{}*sin(2*PI*{}*t),val(ch))", during, BLEEP_GAIN, BLEEP_HZ))
+            .opt("channel_layout", "same"),
+    };
+    Some(FilterChain::new().then(filter).to_string())
+}
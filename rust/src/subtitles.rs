@@ -0,0 +1,461 @@
+use crate::types::{CaptionSegment, WordSpan};
+use crate::video::SubtitleTrack;
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+use tokio::process::Command as TokioCommand;
+
+const MIN_CUE_MS: u64 = 50; // rolling-caption files (YouTube) contain 10 ms "snapshot" cues
+
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+static VTT_TIMESTAMP_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^<((?:\d+:)?\d+:\d+\.\d+)>$").unwrap());
+static ASS_BLOCK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([^}]*)\}").unwrap());
+static ASS_KARAOKE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\\(?:k[fo]?|K)(\d+)").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    /// Pick the format from the file extension, falling back to sniffing the content
+    pub fn detect(path: &Path, content: &str) -> Self {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+            Some("vtt") => SubtitleFormat::Vtt,
+            Some("ass") | Some("ssa") => SubtitleFormat::Ass,
+            Some("srt") => SubtitleFormat::Srt,
+            _ if content.starts_with("WEBVTT") => SubtitleFormat::Vtt,
+            _ if content.contains("[Events]") => SubtitleFormat::Ass,
+            _ => SubtitleFormat::Srt,
+        }
+    }
+}
+
+/// Read a subtitle file into caption segments, ready for build_ass_document
+pub async fn import_subtitles(path: &str) -> anyhow::Result<Vec<CaptionSegment>> {
+    let bytes = tokio::fs::read(path).await
+        .map_err(|e| anyhow::anyhow!("Failed to read subtitle file {}: {}", path, e))?;
+    // Hand-made SRTs are often Windows-1252; keep what decodes rather than refusing the file
+    let content = String::from_utf8_lossy(&bytes);
+    let content = content.trim_start_matches('\u{FEFF}').replace("\r\n", "\n").replace('\r', "\n");
+
+    let format = SubtitleFormat::detect(Path::new(path), &content);
+    let segments = parse_subtitles(&content, format)?;
+    if segments.is_empty() {
+        return Err(anyhow::anyhow!("No subtitle cues found in {}", path));
+    }
+    Ok(segments)
+}
+
+// Common ISO 639-2 container language tags -> the ISO 639-1 codes used everywhere else
+const ISO_639_2_CODES: &[(&str, &str)] = &[
+    ("eng", "en"), ("fre", "fr"), ("fra", "fr"), ("ger", "de"), ("deu", "de"), ("spa", "es"),
+    ("ita", "it"), ("por", "pt"), ("rus", "ru"), ("ukr", "uk"), ("pol", "pl"), ("cze", "cs"),
+    ("ces", "cs"), ("dut", "nl"), ("nld", "nl"), ("swe", "sv"), ("fin", "fi"), ("nor", "no"),
+    ("nob", "no"), ("dan", "da"), ("tur", "tr"), ("ind", "id"), ("gre", "el"), ("ell", "el"),
+    ("jpn", "ja"), ("kor", "ko"), ("chi", "zh"), ("zho", "zh"), ("ara", "ar"), ("heb", "he"),
+    ("hin", "hi"), ("tha", "th"), ("vie", "vi"),
+];
+
+/// Normalize a container language tag ("eng", "en", "English") to ISO 639-1
+pub fn track_language(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    ISO_639_2_CODES.iter()
+        .find(|(long, _)| *long == tag)
+        .map(|(_, code)| code.to_string())
+        .or_else(|| crate::whisper::normalize_language_code(&tag))
+}
+
+/// Pull one embedded subtitle stream out of the container into a file import_subtitles can read.
+/// ASS and WebVTT keep their format (karaoke tags, word timestamps); everything else becomes SRT.
+pub async fn extract_embedded_track(input: &str, track: &SubtitleTrack, out_dir: &Path) -> anyhow::Result<String> {
+    if !track.text_based {
+        return Err(anyhow::anyhow!("Subtitle track {} is a bitmap format ({}) and can't be used as captions", track.index, track.codec));
+    }
+    let (codec, extension) = match track.codec.as_str() {
+        "ass" | "ssa" => ("ass", "ass"),
+        "webvtt" => ("webvtt", "vtt"),
+        _ => ("srt", "srt"),
+    };
+    let out_path = out_dir.join(format!("subtitles_{}.{}", track.index, extension));
+
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let output = TokioCommand::new(ffmpeg_path)
+        .arg("-y").arg("-v").arg("error")
+        .arg("-i").arg(input)
+        .arg("-map").arg(format!("0:{}", track.index))
+        .arg("-c:s").arg(codec)
+        .arg(&out_path)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffmpeg failed to extract subtitle track {}: {}", track.index, String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(out_path.to_string_lossy().to_string())
+}
+
+pub fn parse_subtitles(content: &str, format: SubtitleFormat) -> anyhow::Result<Vec<CaptionSegment>> {
+    let mut cues = match format {
+        SubtitleFormat::Srt => parse_srt(content),
+        SubtitleFormat::Vtt => parse_vtt(content),
+        SubtitleFormat::Ass => parse_ass(content)?,
+    };
+    cues.sort_by_key(|c| c.start_ms);
+
+    // Drop snapshot cues and join back-to-back repeats of the same line
+    let mut segments: Vec<CaptionSegment> = Vec::with_capacity(cues.len());
+    for cue in cues {
+        if cue.end_ms < cue.start_ms + MIN_CUE_MS || cue.text.is_empty() {
+            continue;
+        }
+        if let Some(prev) = segments.last_mut() {
+            if prev.text == cue.text && cue.start_ms <= prev.end_ms + MIN_CUE_MS {
+                prev.end_ms = prev.end_ms.max(cue.end_ms);
+                continue;
+            }
+        }
+        segments.push(cue);
+    }
+    Ok(segments)
+}
+
+/// `01:02:03,456` (SRT), `01:02:03.456` / `02:03.456` (WebVTT) or `1:02:03.45` (ASS) in ms
+fn parse_timestamp(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (clock, fraction) = s.split_once([',', '.']).unwrap_or((s, "0"));
+    let mut parts = clock.split(':').map(|p| p.parse::<u64>().ok());
+    let (h, m, sec) = match (parts.next()?, parts.next(), parts.next(), parts.next()) {
+        (h, Some(m), Some(sec), None) => (h?, m?, sec?),
+        (m, Some(sec), None, None) => (0, m?, sec?),
+        _ => return None,
+    };
+    if fraction.is_empty() || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // Scale the fraction to milliseconds whatever its precision
+    let digits: String = fraction.chars().chain("000".chars()).take(3).collect();
+    let ms = digits.parse::<u64>().ok()?;
+    Some(((h * 60 + m) * 60 + sec) * 1000 + ms)
+}
+
+/// `00:00:01,000 --> 00:00:04,000 [settings]`
+fn parse_cue_times(line: &str) -> Option<(u64, u64)> {
+    let (start, rest) = line.split_once("-->")?;
+    Some((parse_timestamp(start)?, parse_timestamp(rest.split_whitespace().next()?)?))
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", "\u{00A0}")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Timed stretches of cue text -> words. A stretch that starts without a space
+/// continues the previous word (karaoke syllables: "Hel" + "lo").
+fn timed_chunks_to_words(chunks: &[(u64, u64, String)]) -> Vec<WordSpan> {
+    let mut words: Vec<WordSpan> = Vec::new();
+    let mut mid_word = false;
+
+    for (start, end, text) in chunks {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        if tokens.is_empty() {
+            mid_word &= text.is_empty();
+            continue;
+        }
+        let continues = mid_word && !text.starts_with(char::is_whitespace);
+        let per = end.saturating_sub(*start) / tokens.len() as u64;
+
+        for (k, token) in tokens.iter().enumerate() {
+            let word_start = start + per * k as u64;
+            let word_end = if k + 1 == tokens.len() { *end } else { word_start + per };
+            if k == 0 && continues {
+                if let Some(last) = words.last_mut() {
+                    last.text.push_str(token);
+                    last.end_ms = word_end;
+                    continue;
+                }
+            }
+            words.push(WordSpan { start_ms: word_start, end_ms: word_end, text: token.to_string(), speaker: None, confidence: None, hidden: false });
+        }
+        mid_word = !text.ends_with(char::is_whitespace);
+    }
+    words
+}
+
+fn cue_blocks(content: &str) -> impl Iterator<Item = Vec<&str>> {
+    content.split("\n\n")
+        .map(|block| block.lines().filter(|l| !l.trim().is_empty()).collect::<Vec<_>>())
+        .filter(|lines| !lines.is_empty())
+}
+
+fn parse_srt(content: &str) -> Vec<CaptionSegment> {
+    cue_blocks(content)
+        .filter_map(|lines| {
+            // The numeric index line is optional in practice
+            let timing = lines.iter().position(|l| l.contains("-->"))?;
+            let (start_ms, end_ms) = parse_cue_times(lines[timing])?;
+            let text = lines[timing + 1..].join(" ");
+            let text = ASS_BLOCK_RE.replace_all(&TAG_RE.replace_all(&text, ""), "").to_string();
+            Some(CaptionSegment {
+                start_ms,
+                end_ms,
+                text: collapse_whitespace(&decode_entities(&text)),
+                words: Vec::new(),
+                language: None,
+                speaker: None,
+                confidence: None,
+                hidden: false,
+            })
+        })
+        .collect()
+}
+
+fn parse_vtt(content: &str) -> Vec<CaptionSegment> {
+    cue_blocks(content)
+        .filter(|lines| !["WEBVTT", "NOTE", "STYLE", "REGION"].iter().any(|k| lines[0].starts_with(k)))
+        .filter_map(|lines| {
+            let timing = lines.iter().position(|l| l.contains("-->"))?;
+            let (start_ms, end_ms) = parse_cue_times(lines[timing])?;
+            let mut text_lines: Vec<&str> = lines[timing + 1..].to_vec();
+
+            // Rolling captions repeat the previous line untimed above the new, word-timed one
+            let has_word_times = text_lines.iter().any(|l| TAG_RE.find_iter(l).any(|m| VTT_TIMESTAMP_RE.is_match(m.as_str())));
+            if has_word_times {
+                text_lines.retain(|l| TAG_RE.find_iter(l).any(|m| VTT_TIMESTAMP_RE.is_match(m.as_str())));
+            }
+            let joined = text_lines.join(" ");
+
+            // Cut the text at every <00:00:01.000> tag; other tags (<c>, <v Name>, <i>) are dropped
+            let mut chunks: Vec<(u64, u64, String)> = Vec::new();
+            let mut chunk_start = start_ms;
+            let mut chunk_text = String::new();
+            let mut cursor = 0;
+            for tag in TAG_RE.find_iter(&joined) {
+                chunk_text.push_str(&joined[cursor..tag.start()]);
+                cursor = tag.end();
+                if let Some(ts) = VTT_TIMESTAMP_RE.captures(tag.as_str()).and_then(|c| parse_timestamp(&c[1])) {
+                    let ts = ts.clamp(chunk_start, end_ms);
+                    chunks.push((chunk_start, ts, decode_entities(&std::mem::take(&mut chunk_text))));
+                    chunk_start = ts;
+                }
+            }
+            chunk_text.push_str(&joined[cursor..]);
+            chunks.push((chunk_start, end_ms, decode_entities(&chunk_text)));
+
+            let text = collapse_whitespace(&chunks.iter().map(|c| c.2.as_str()).collect::<String>());
+            let words = if has_word_times { timed_chunks_to_words(&chunks) } else { Vec::new() };
+            Some(CaptionSegment { start_ms, end_ms, text, words, language: None, speaker: None, confidence: None, hidden: false })
+        })
+        .collect()
+}
+
+/// ASS line breaks and hard spaces inside dialogue text
+fn clean_ass_text(text: &str) -> String {
+    text.replace("\\N", " ").replace("\\n", " ").replace("\\h", " ")
+}
+
+fn parse_ass(content: &str) -> anyhow::Result<Vec<CaptionSegment>> {
+    let mut in_events = false;
+    let mut fields: Vec<String> = Vec::new();
+    let mut segments = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[Events]");
+            continue;
+        }
+        if !in_events {
+            continue;
+        }
+        if let Some(format) = line.strip_prefix("Format:") {
+            fields = format.split(',').map(|f| f.trim().to_ascii_lowercase()).collect();
+            continue;
+        }
+        let Some(dialogue) = line.strip_prefix("Dialogue:") else { continue };
+        if fields.is_empty() {
+            return Err(anyhow::anyhow!("ASS [Events] section has no Format line"));
+        }
+
+        // Text is always the last field and may itself contain commas
+        let values: Vec<&str> = dialogue.splitn(fields.len(), ',').map(str::trim).collect();
+        let field = |name: &str| fields.iter().position(|f| f == name).and_then(|i| values.get(i).copied());
+        let (Some(start_ms), Some(end_ms), Some(raw)) = (
+            field("start").and_then(parse_timestamp),
+            field("end").and_then(parse_timestamp),
+            field("text"),
+        ) else { continue };
+
+        // {\k50}Hel{\k30}lo: each karaoke tag times the syllable after it, in centiseconds
+        let mut chunks: Vec<(u64, u64, String)> = Vec::new();
+        let mut plain = String::new();
+        let mut karaoke = false;
+        let mut cursor = 0;
+        for block in ASS_BLOCK_RE.captures_iter(raw) {
+            let whole = block.get(0).unwrap();
+            let between = clean_ass_text(&raw[cursor..whole.start()]);
+            plain.push_str(&between);
+            if let Some(last) = chunks.last_mut() {
+                last.2.push_str(&between);
+            }
+            cursor = whole.end();
+
+            if let Some(k) = ASS_KARAOKE_RE.captures(&block[1]).and_then(|c| c[1].parse::<u64>().ok()) {
+                karaoke = true;
+                let chunk_start = chunks.last().map_or(start_ms, |c| c.1);
+                let chunk_end = (chunk_start + k * 10).min(end_ms);
+                chunks.push((chunk_start, chunk_end, String::new()));
+            }
+        }
+        let tail = clean_ass_text(&raw[cursor..]);
+        plain.push_str(&tail);
+        if let Some(last) = chunks.last_mut() {
+            last.2.push_str(&tail);
+        }
+
+        segments.push(CaptionSegment {
+            start_ms,
+            end_ms,
+            text: collapse_whitespace(&plain),
+            words: if karaoke { timed_chunks_to_words(&chunks) } else { Vec::new() },
+            language: None,
+            speaker: None,
+            confidence: None,
+            hidden: false,
+        });
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(segment: &CaptionSegment) -> Vec<(&str, u64, u64)> {
+        segment.words.iter().map(|w| (w.text.as_str(), w.start_ms, w.end_ms)).collect()
+    }
+
+    #[test]
+    fn timestamps_in_every_format() {
+        assert_eq!(parse_timestamp("01:02:03,456"), Some(3_723_456));
+        assert_eq!(parse_timestamp("01:02:03.456"), Some(3_723_456));
+        assert_eq!(parse_timestamp("02:03.456"), Some(123_456));
+        assert_eq!(parse_timestamp("1:02:03.45"), Some(3_723_450));
+        assert_eq!(parse_timestamp("1:02:03"), Some(3_723_000));
+        assert_eq!(parse_timestamp("1:02:03.4x"), None);
+        assert_eq!(parse_timestamp("soon"), None);
+    }
+
+    #[test]
+    fn srt_cues_strip_tags_and_entities() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,500\n<i>Hello</i> &amp;\n{\\an8}welcome\n\n2\n00:00:03,000 --> 00:00:04,000\nSecond cue\n";
+        let segments = parse_subtitles(srt, SubtitleFormat::Srt).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "Hello & welcome");
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1000, 2500));
+        assert!(segments[0].words.is_empty());
+        assert_eq!(segments[1].text, "Second cue");
+    }
+
+    #[test]
+    fn snapshot_cues_and_repeats_are_merged() {
+        let srt = "00:00:01,000 --> 00:00:02,000\nSame line\n\n\
+                   00:00:02,000 --> 00:00:02,010\nSnapshot\n\n\
+                   00:00:02,000 --> 00:00:03,000\nSame line\n";
+        let segments = parse_subtitles(srt, SubtitleFormat::Srt).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1000, 3000));
+    }
+
+    #[test]
+    fn vtt_inline_timestamps_become_words() {
+        let vtt = "WEBVTT\n\nNOTE a comment\n\n00:00:01.000 --> 00:00:03.000 align:start\n<c>Hello</c><00:00:01.500> big<00:00:02.000> world\n";
+        let segments = parse_subtitles(vtt, SubtitleFormat::Vtt).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "Hello big world");
+        assert_eq!(words(&segments[0]), [("Hello", 1000, 1500), ("big", 1500, 2000), ("world", 2000, 3000)]);
+    }
+
+    #[test]
+    fn vtt_rolling_captions_drop_the_repeated_line() {
+        let vtt = "WEBVTT\n\n00:00:04.000 --> 00:00:06.000\nprevious line\nnext<00:00:05.000> words\n";
+        let segments = parse_subtitles(vtt, SubtitleFormat::Vtt).unwrap();
+        assert_eq!(segments[0].text, "next words");
+        assert_eq!(words(&segments[0]), [("next", 4000, 5000), ("words", 5000, 6000)]);
+    }
+
+    #[test]
+    fn vtt_without_word_times_has_no_words() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:02.000\n<v Ann>Hi &lt;there&gt;\n";
+        let segments = parse_subtitles(vtt, SubtitleFormat::Vtt).unwrap();
+        assert_eq!(segments[0].text, "Hi <there>");
+        assert!(segments[0].words.is_empty());
+    }
+
+    #[test]
+    fn ass_karaoke_syllables_join_into_words() {
+        let ass = "[Script Info]\nTitle: test\n\n[Events]\n\
+                   Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n\
+                   Dialogue: 0,0:00:01.00,0:00:03.00,Default,,0,0,0,,{\\k50}Hel{\\k30}lo {\\k100}world, friend\n";
+        let segments = parse_subtitles(ass, SubtitleFormat::Ass).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "Hello world, friend");
+        assert_eq!(words(&segments[0]), [("Hello", 1000, 1800), ("world,", 1800, 2300), ("friend", 2300, 2800)]);
+    }
+
+    #[test]
+    fn ass_plain_dialogue_keeps_line_breaks_as_spaces() {
+        let ass = "[Events]\nFormat: Start, End, Text\nDialogue: 0:00:00.50,0:00:02.00,{\\i1}Two\\Nlines\n";
+        let segments = parse_subtitles(ass, SubtitleFormat::Ass).unwrap();
+        assert_eq!(segments[0].text, "Two lines");
+        assert_eq!(segments[0].start_ms, 500);
+        assert!(segments[0].words.is_empty());
+    }
+
+    #[test]
+    fn ass_without_format_line_is_an_error() {
+        assert!(parse_subtitles("[Events]\nDialogue: 0,0:00:01.00,0:00:02.00,,,0,0,0,,Hi\n", SubtitleFormat::Ass).is_err());
+    }
+
+    #[test]
+    fn format_detection_prefers_extension_then_content() {
+        assert_eq!(SubtitleFormat::detect(Path::new("a.VTT"), ""), SubtitleFormat::Vtt);
+        assert_eq!(SubtitleFormat::detect(Path::new("a.ssa"), ""), SubtitleFormat::Ass);
+        assert_eq!(SubtitleFormat::detect(Path::new("a.txt"), "WEBVTT\n"), SubtitleFormat::Vtt);
+        assert_eq!(SubtitleFormat::detect(Path::new("a"), "[Events]\n"), SubtitleFormat::Ass);
+        assert_eq!(SubtitleFormat::detect(Path::new("a"), "1\n00:00:01,000 --> 00:00:02,000\n"), SubtitleFormat::Srt);
+    }
+
+    #[test]
+    fn container_language_tags_normalize_to_iso_639_1() {
+        assert_eq!(track_language("eng").as_deref(), Some("en"));
+        assert_eq!(track_language(" GER ").as_deref(), Some("de"));
+        assert_eq!(track_language("fra").as_deref(), Some("fr"));
+        assert_eq!(track_language("ja").as_deref(), Some("ja"));
+        assert_eq!(track_language("Spanish").as_deref(), Some("es"));
+        assert_eq!(track_language("xyz"), None);
+    }
+
+    #[tokio::test]
+    async fn bitmap_tracks_are_not_extracted() {
+        let track = SubtitleTrack {
+            index: 3,
+            codec: "hdmv_pgs_subtitle".into(),
+            language: Some("eng".into()),
+            title: None,
+            default: true,
+            forced: false,
+            text_based: false,
+        };
+        let err = extract_embedded_track("missing.mkv", &track, &std::env::temp_dir()).await.unwrap_err();
+        assert!(err.to_string().contains("bitmap"));
+    }
+}
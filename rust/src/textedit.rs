@@ -0,0 +1,205 @@
+use crate::jumpcut;
+use crate::rpc::RpcEvent;
+use crate::types::{ApplyTextEditsParams, ApplyTextEditsResult, CaptionSegment, TimeRange, WordRange};
+use crate::video::{self, HardwareEncoder};
+use tokio::process::Command as TokioCommand;
+
+const DEFAULT_CROSSFADE_MS: u64 = 30; // long enough to hide the click, short enough not to smear words
+const EDIT_CRF: &str = "18";
+const EDIT_PRESET: &str = "medium";
+
+// Start and end of every word, in the order `WordRange` counts them
+fn word_times(segments: &[CaptionSegment]) -> Vec<(u64, u64)> {
+    segments.iter()
+        .flat_map(|s| {
+            if s.words.is_empty() {
+                vec![(s.start_ms, s.end_ms)]
+            } else {
+                s.words.iter().map(|w| (w.start_ms, w.end_ms)).collect()
+            }
+        })
+        .collect()
+}
+
+fn deleted_flags(word_count: usize, deleted: &[WordRange]) -> anyhow::Result<Vec<bool>> {
+    let mut flags = vec![false; word_count];
+    for range in deleted {
+        if range.start > range.end || range.end > word_count {
+            return Err(anyhow::anyhow!("Deleted words {}..{} are outside the transcription ({} words)",
+                range.start, range.end, word_count));
+        }
+        flags[range.start..range.end].fill(true);
+    }
+    Ok(flags)
+}
+
+/// Edit decision list for the deleted words: sorted source ranges to remove.
+/// A cut runs from the first deleted word up to the next kept word, so the pause after
+/// the deletion goes with it and the pause before stays as the natural gap. Deleting the
+/// opening or closing words also takes the silence before or after them. Whatever would be
+/// left between two cuts that is shorter than `min_keep_ms` is cut too.
+pub fn plan_cuts(
+    segments: &[CaptionSegment],
+    deleted: &[WordRange],
+    duration_ms: u64,
+    min_keep_ms: u64,
+) -> anyhow::Result<Vec<TimeRange>> {
+    let words = word_times(segments);
+    let flags = deleted_flags(words.len(), deleted)?;
+
+    let mut cuts: Vec<TimeRange> = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        if !flags[i] {
+            i += 1;
+            continue;
+        }
+        let first = i;
+        while i < words.len() && flags[i] {
+            i += 1;
+        }
+        let start_ms = if first == 0 { 0 } else { words[first].0 };
+        let end_ms = match words.get(i) {
+            Some(next) => next.0,
+            None => duration_ms,
+        };
+        if end_ms > start_ms {
+            cuts.push(TimeRange { start_ms, end_ms: end_ms.min(duration_ms) });
+        }
+    }
+    cuts.sort_by_key(|c| c.start_ms);
+
+    let mut merged: Vec<TimeRange> = Vec::with_capacity(cuts.len());
+    for cut in cuts {
+        match merged.last_mut() {
+            Some(last) if cut.start_ms < last.end_ms + min_keep_ms => last.end_ms = last.end_ms.max(cut.end_ms),
+            _ => merged.push(cut),
+        }
+    }
+    if let Some(first) = merged.first_mut().filter(|c| c.start_ms < min_keep_ms) {
+        first.start_ms = 0;
+    }
+    if let Some(last) = merged.last_mut().filter(|c| c.end_ms + min_keep_ms > duration_ms) {
+        last.end_ms = duration_ms;
+    }
+    merged.retain(|c| c.end_ms > c.start_ms);
+    Ok(merged)
+}
+
+/// Drop the deleted words from the captions and move the rest onto the edited timeline
+pub fn remap_edited(segments: &mut Vec<CaptionSegment>, deleted: &[WordRange], cuts: &[TimeRange]) -> anyhow::Result<()> {
+    let word_count = word_times(segments).len();
+    let flags = deleted_flags(word_count, deleted)?;
+
+    let mut index = 0;
+    let mut keep_segment = Vec::with_capacity(segments.len());
+    for seg in segments.iter_mut() {
+        if seg.words.is_empty() {
+            keep_segment.push(!flags[index]);
+            index += 1;
+            continue;
+        }
+        let before = seg.words.len();
+        let mut word_flags = flags[index..index + before].iter();
+        seg.words.retain(|_| !word_flags.next().copied().unwrap_or(false));
+        index += before;
+        if seg.words.len() != before && !seg.words.is_empty() {
+            seg.start_ms = seg.words[0].start_ms;
+            seg.end_ms = seg.words[seg.words.len() - 1].end_ms;
+            seg.text = seg.words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+        }
+        keep_segment.push(!seg.words.is_empty());
+    }
+    let mut keep = keep_segment.into_iter();
+    segments.retain(|_| keep.next().unwrap_or(true));
+
+    jumpcut::remap_segments(segments, cuts);
+    Ok(())
+}
+
+/// Render the video without the deleted transcript words and return captions for the new timeline
+pub async fn apply_text_edits(id: &str, p: ApplyTextEditsParams, mut emit: impl FnMut(RpcEvent)) -> anyhow::Result<ApplyTextEditsResult> {
+    let pr = video::probe(id, &p.input_video, &mut emit).await?;
+    let duration_ms = pr.duration
+        .map(|d| (d * 1000.0) as u64)
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the duration of {}", p.input_video))?;
+    let crossfade_ms = p.crossfade_ms.unwrap_or(DEFAULT_CROSSFADE_MS);
+
+    let cuts = plan_cuts(&p.segments, &p.deleted, duration_ms, crossfade_ms)?;
+    let keeps = jumpcut::keep_ranges(&cuts, duration_ms);
+    if keeps.is_empty() {
+        return Err(anyhow::anyhow!("Every word is deleted, nothing would be left of the video"));
+    }
+    let out = p.out.clone().unwrap_or_else(|| {
+        let stem = std::path::Path::new(&p.input_video).with_extension("");
+        format!("{}_edited.mp4", stem.to_string_lossy())
+    });
+
+    let removed_ms: u64 = cuts.iter().map(|c| c.end_ms - c.start_ms).sum();
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Cutting {} ranges ({:.1}s) out of {}", cuts.len(), removed_ms as f64 / 1000.0, p.input_video)
+    });
+
+    let graph = jumpcut::edit_graph(&keeps, pr.video.then_some("[0:v:0]"), pr.audio.then_some("[0:a:0]"), crossfade_ms);
+    let gop_size = pr.fps.map_or(48, |fps| (fps * 2.0).round() as u32).to_string();
+    let encoder = video::get_best_hardware_encoder().await;
+    emit(RpcEvent::Progress { id: id.into(), status: "Rendering edited video...".into(), progress: 0.1 });
+
+    let mut result = render(&p.input_video, &graph, &pr, encoder, &gop_size, &out).await;
+    if result.is_err() && !matches!(encoder, HardwareEncoder::Software) {
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: "Hardware encoder failed, falling back to software encoding (libx264)".into()
+        });
+        result = render(&p.input_video, &graph, &pr, HardwareEncoder::Software, &gop_size, &out).await;
+    }
+    result?;
+
+    let mut segments = p.segments;
+    remap_edited(&mut segments, &p.deleted, &cuts)?;
+    let full_text = segments.iter().map(|s| s.text.trim()).collect::<Vec<_>>().join(" ");
+    emit(RpcEvent::Progress { id: id.into(), status: "Edited video ready".into(), progress: 1.0 });
+
+    Ok(ApplyTextEditsResult {
+        video: out,
+        segments,
+        full_text,
+        duration: Some((duration_ms - removed_ms) as f64 / 1000.0),
+        cuts,
+    })
+}
+
+async fn render(
+    input: &str,
+    graph: &str,
+    pr: &video::ProbeResult,
+    encoder: HardwareEncoder,
+    gop_size: &str,
+    out: &str,
+) -> anyhow::Result<()> {
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let mut cmd = TokioCommand::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error")
+       .arg("-i").arg(input)
+       .arg("-filter_complex").arg(graph);
+    if pr.video {
+        cmd.arg("-map").arg("[vout]");
+        cmd.args(video::get_hardware_encoder_args(encoder, EDIT_CRF, gop_size, EDIT_PRESET));
+    }
+    if pr.audio {
+        cmd.arg("-map").arg("[aout]")
+           .arg("-c:a").arg("aac")
+           .arg("-b:a").arg("160k");
+    }
+    let output = cmd
+        .arg("-map_metadata").arg("0")
+        .arg("-movflags").arg("+faststart")
+        .arg(out)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffmpeg failed to render the edited video: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(())
+}
@@ -10,6 +10,18 @@ pub struct CaptionSegment {
     // Optional word-level timing (used when split_by_words = true)
     #[serde(default)]
     pub words: Vec<WordSpan>,
+    // ISO 639-1 code of the caption text's language, when known ("en" for translations)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // Speaker number (0, 1, ...) when speaker detection ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+    // Recognition confidence (0.0 - 1.0) when the backend reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    // Filler kept for its timing but not shown (filler cleanup in "hide" mode)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,18 +30,64 @@ pub struct WordSpan {
     pub start_ms: u64,
     pub end_ms: u64,
     pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// What whisper should produce from the audio
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionTask {
+    #[default]
+    Transcribe,   // Text in the spoken language
+    Translate,    // English translation of the speech
+}
+
+impl TranscriptionTask {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptionTask::Transcribe => "transcribe",
+            TranscriptionTask::Translate => "translate",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TranscribeSegmentsParams {
     pub audio: String,                            // Path to audio file to transcribe
     pub model: Option<String>,                    // Whisper model to use (default: "whisper-1")
-    pub language: Option<String>,                 // Language hint for better accuracy
+    pub language: Option<String>,                 // Language hint, or "auto" to detect
     pub split_by_words: bool,                     // Whether to split by words or segments
     pub api_key: Option<String>,                  // OpenAI API key
     pub prompt: Option<String>,                   // Context prompt to improve accuracy
     pub video_file: Option<String>,               // Original video file path (for JSON output location)
+    #[serde(default)]
+    pub task: TranscriptionTask,                  // "transcribe" (default) or "translate" to English
+    #[serde(default)]
+    pub project: Option<String>,                  // Project whose vocabulary is added to the prompt
+    #[serde(default)]
+    pub backend: Option<String>,                  // Force one backend by name (e.g. "whisper-cpp")
+    #[serde(default)]
+    pub backend_preference: Option<Vec<String>>,  // Ordered backends to try (default: registry order)
+    #[serde(default)]
+    pub api_endpoint: Option<ApiEndpointConfig>,  // OpenAI-compatible server settings (default: OpenAI)
+    #[serde(default)]
+    pub diarize: bool,                            // Detect speaker turns (tinydiarize model or stereo input)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiEndpointConfig {
+    pub base_url: Option<String>,                 // e.g. "http://localhost:8000/v1" (default: "https://api.openai.com/v1")
+    pub model: Option<String>,                    // Model name sent to the server (default: "whisper-1")
+    #[serde(default)]
+    pub headers: std::collections::HashMap<String, String>, // Extra request headers
+    pub max_upload_bytes: Option<u64>,            // Provider upload limit (default: 25 MB)
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,6 +97,30 @@ pub struct TranscribeSegmentsResult {
     pub full_text: String,                        // Complete transcription text
     pub duration: Option<f64>,                    // Total audio duration
     pub json_file: String,                        // Path to saved JSON captions file
+    pub language: Option<String>,                 // Detected (or forced) language as ISO 639-1 code
+    #[serde(default)]
+    pub low_confidence_words: Vec<LowConfidenceWord>, // Words worth a second look, in time order
+}
+
+// A word the recognizer was unsure about, for review in an editor
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LowConfidenceWord {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub confidence: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendCapabilities {
+    pub word_timestamps: bool,                    // Produces word-level timings
+    pub translation: bool,                        // Supports task = "translate"
+    pub language_detect: bool,                    // Reports the detected language
+    pub prompt: bool,                             // Accepts an initial prompt
+    pub parallel_chunks: bool,                    // Safe to run on several chunks at once
+    pub diarization: bool,                        // Can label speaker turns
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -51,15 +133,62 @@ pub struct BurnResult {
 #[serde(rename_all = "camelCase")]
 pub struct WhisperCacheEntry {
     pub audio_hash: String,                       // blake3 hash of audio file content
-    pub params_hash: String,                      // blake3 hash of transcription parameters
+    pub params_hash: String,                      // blake3 hash of transcription parameters, backend and model
     pub response_path: String,                    // path to cached JSON response file
-    pub timestamp: u64,                           // unix timestamp for LRU eviction
+    pub timestamp: u64,                           // unix timestamp of the last use, for LRU eviction
+    #[serde(default)]
+    pub backend: String,                          // Backend that produced the response
+    #[serde(default)]
+    pub model: String,                            // Model that actually ran (after fallbacks)
+    #[serde(default)]
+    pub size: u64,                                // Size of the response file in bytes
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct WhisperCacheIndex {
     pub entries: Vec<WhisperCacheEntry>,
+    #[serde(default)]
+    pub max_bytes: Option<u64>,                   // Size budget set by the user (default: 200 MB)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub dir: String,                              // Where cached transcriptions are stored
+    pub entries: usize,                           // Number of cached transcriptions
+    pub bytes: u64,                               // Total size of cached transcriptions
+    pub max_bytes: u64,                           // Size budget; least recently used entries go first
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetCacheBudgetParams {
+    pub max_bytes: Option<u64>,                   // New size budget in bytes, or null for the default
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearCacheResult {
+    pub removed: usize,                           // Cached transcriptions deleted
+    pub freed: u64,                               // Bytes freed
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderCacheEntry {
+    pub output_path: String,                      // path of the rendered captioned video
+    pub render_hash: String,                      // blake3 hash of ASS document + input video + encoder settings
+    #[serde(default)]
+    pub encoder: String,                          // encoder that actually produced the file (after any fallback)
+    pub size: u64,                                // output file size, used to detect external modification
+    pub timestamp: u64,                           // unix timestamp of the render
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderCacheIndex {
+    pub entries: Vec<RenderCacheEntry>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -69,6 +198,13 @@ pub struct WhisperSegment {
     pub start: f64,
     pub end: f64,
     pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<u32>,
+    // Mean token log probability (OpenAI verbose JSON)
+    #[serde(default, alias = "avg_logprob", skip_serializing_if = "Option::is_none")]
+    pub avg_logprob: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -77,6 +213,9 @@ pub struct WhisperWord {
     pub word: String,
     pub start: f64,
     pub end: f64,
+    // Probability (0.0 - 1.0); for merged subwords, that of the least certain piece
+    #[serde(default, alias = "probability", skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -113,7 +252,7 @@ pub struct GenerateCaptionsParams {
     pub font_name: Option<String>,        // Font name for captions (defaults to "Montserrat Black")
     pub split_by_words: bool,             // Whether to split transcription by words or segments
     pub model: Option<String>,            // Whisper model to use (default: "whisper-1")
-    pub language: Option<String>,         // Language hint for better accuracy
+    pub language: Option<String>,         // Language hint, or "auto" to detect
     pub prompt: Option<String>,           // Context prompt to improve accuracy
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text_color: Option<String>,       // Text color as hex string (e.g., "#ffffff")
@@ -126,15 +265,132 @@ pub struct GenerateCaptionsParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<String>,         // Caption position: "bottom" or "center"
     pub api_key: Option<String>,         // OpenAI API key
+    #[serde(default)]
+    pub task: TranscriptionTask,          // "transcribe" (default) or "translate" to English
+    #[serde(default)]
+    pub bilingual: bool,                  // Render original captions plus an English translation line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translation_color: Option<String>, // Translation line color as hex string (bilingual mode)
+    #[serde(default)]
+    pub project: Option<String>,          // Project whose vocabulary is added to the prompt
+    #[serde(default)]
+    pub backend: Option<String>,          // Force one transcription backend by name
+    #[serde(default)]
+    pub backend_preference: Option<Vec<String>>, // Ordered transcription backends to try
+    #[serde(default)]
+    pub api_endpoint: Option<ApiEndpointConfig>, // OpenAI-compatible server settings
+    #[serde(default)]
+    pub subtitle_file: Option<String>,    // Existing .srt/.vtt/.ass to burn in instead of transcribing
+    #[serde(default)]
+    pub subtitle_track: Option<u32>,      // Embedded subtitle stream index (from probe) to burn in instead of transcribing
+    #[serde(default)]
+    pub diarize: bool,                    // Detect speaker turns and style each speaker's captions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_colors: Option<Vec<String>>, // Text color per speaker as hex strings (speaker 0 first)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_positions: Option<Vec<String>>, // Caption position per speaker: "bottom" or "center"
+    #[serde(default)]
+    pub multitrack: Option<MultitrackSource>, // Transcribe each audio stream or channel as its own speaker
+    #[serde(default)]
+    pub mark_low_confidence: bool,        // Underline low-confidence words in red, for a review preview
+    #[serde(default)]
+    pub redaction: Option<RedactionParams>, // Mask profanity and personal data in captions (and optionally audio)
+    #[serde(default)]
+    pub filler_cleanup: Option<FillerCleanupParams>, // Drop "um"/"uh" and stutter repeats from captions
+    #[serde(default)]
+    pub jump_cuts: Option<JumpCutParams>, // Cut long pauses out of the exported videos
+}
+
+/// Jump-cut settings
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct JumpCutParams {
+    #[serde(default)]
+    pub min_pause_ms: Option<u64>,        // Pauses between words longer than this are cut (default 700)
+    #[serde(default)]
+    pub padding_ms: Option<u64>,          // Kept before and after speech around each cut (default 150)
+    #[serde(default)]
+    pub confirm_silence: bool,            // Only cut where ffmpeg silencedetect also hears silence
+}
+
+/// A stretch of the source timeline, in milliseconds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Filler-word and repeat cleanup
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FillerCleanupParams {
+    #[serde(default)]
+    pub mode: FillerMode,                 // "remove" (default) or "hide"
+    #[serde(default)]
+    pub extra_words: Vec<String>,         // Added to the caption language's filler list
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FillerMode {
+    #[default]
+    Remove,   // Delete the words; neighbours take over their time
+    Hide,     // Keep the words and their timing, flagged hidden
+}
+
+/// What to redact and how
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionParams {
+    #[serde(default)]
+    pub profanity: bool,                  // Mask words from the profanity list
+    #[serde(default)]
+    pub profanity_words: Option<Vec<String>>, // Replaces the built-in (English) profanity list
+    #[serde(default)]
+    pub pii: bool,                        // Mask emails, phone numbers and card-like numbers
+    #[serde(default)]
+    pub mask: RedactionMask,              // How masked text looks
+    #[serde(default)]
+    pub audio: RedactionAudio,            // What happens to the audio under redacted words
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionMask {
+    #[default]
+    Partial,  // "f***", "***-***-****"
+    Label,    // "[redacted]"
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionAudio {
+    #[default]
+    None,     // Captions only
+    Bleep,    // Replace the words with a tone
+    Mute,     // Silence the words
+}
+
+/// Where each speaker's microphone lives in a multitrack recording
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MultitrackSource {
+    Streams,  // one audio stream per speaker
+    Channels, // one channel per speaker in the first audio stream
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GenerateCaptionsResult {
     pub probe_result: crate::video::ProbeResult,  // Original video information
-    pub audio_file: String,               // Path to extracted audio file
-    pub transcription: TranscribeSegmentsResult,  // Transcription results and segments
+    pub audio_file: String,               // Path to extracted audio file (empty when subtitles were imported)
+    pub transcription: TranscribeSegmentsResult,  // Transcription results and segments (or the imported cues)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translation: Option<TranscribeSegmentsResult>, // English translation pass (bilingual mode)
     pub captioned_videos: Vec<CaptionedVideoResult>, // List of generated videos with captions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cuts: Vec<TimeRange>,             // Source ranges cut out by jump cuts (caption times are after the cuts)
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -145,13 +401,19 @@ pub struct CaptionedVideoResult {
     pub captioned_video: String,          // Path to final video with captions
     pub width: u32,                       // Video width
     pub height: u32,                      // Video height
+    #[serde(default)]
+    pub cached: bool,                     // True if the output was reused from the render cache
 }
 
 // Model download types
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DownloadModelParams {
-    pub model: String,                    // Model name: "tiny", "base", "small", "medium", "large"
+    pub model: String,                    // Registry name: "base", "small.en", "large-v3-turbo-q5_0"... ("large" = "large-v3")
+    #[serde(default)]
+    pub mirror_url: Option<String>,       // Base URL serving the ggml-*.bin files (default: huggingface.co)
+    #[serde(default)]
+    pub proxy: Option<String>,            // HTTP(S) proxy URL, e.g. "http://proxy.corp:3128"
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -161,3 +423,184 @@ pub struct DownloadModelResult {
     pub path: String,                     // Path where model was saved
     pub size: u64,                        // Downloaded file size in bytes
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteModelParams {
+    pub model: String,                    // Model name, as listed by listModels
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteModelResult {
+    pub model: String,                    // Model name that was deleted
+    pub freed: u64,                       // Bytes removed, including any partial download (0 for registered files, which are only forgotten)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportModelParams {
+    pub path: String,                     // ggml .bin file to copy into the models dir
+    #[serde(default)]
+    pub name: Option<String>,             // Name to install it under (default: from the file name, or the registry model it matches)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterModelParams {
+    pub name: String,                     // Name to use as `model` in transcription requests
+    pub path: String,                     // ggml .bin file anywhere on disk; it is used in place
+    #[serde(default)]
+    pub english_only: bool,               // Model was trained on English only
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelLanguages {
+    Multilingual, // All whisper languages, translation included
+    English,      // ".en" models: English only, a bit more accurate at small sizes
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelQuantization {
+    F16,      // Full-size weights as released
+    Q5_0,
+    Q5_1,
+    Q8_0,
+}
+
+// Models the user pointed us at, kept in the app data dir
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomModelStore {
+    pub models: Vec<CustomModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomModel {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub english_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub name: String,                     // Model name, as used in transcription requests
+    pub size: u64,                        // File size in bytes, or the approximate download size if not installed
+    pub path: Option<String>,             // Full path to the model file, if installed
+    pub installed: bool,                  // The model file is on disk
+    pub verified: bool,                   // Checksum matches the registry
+    pub languages: ModelLanguages,        // What the model can transcribe
+    pub quantization: ModelQuantization,  // Weight format (quantized models are smaller and faster)
+    pub custom: bool,                     // Registered by the user rather than from the registry
+}
+
+// Checksums already computed for model files, keyed by full path
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelChecksumIndex {
+    pub files: std::collections::HashMap<String, ModelChecksum>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelChecksum {
+    pub size: u64,                        // File size when hashed
+    pub modified: u64,                    // Modification time (unix seconds) when hashed
+    pub sha1: String,
+}
+
+// Custom vocabulary types
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct VocabularyStore {
+    pub projects: std::collections::HashMap<String, Vec<String>>, // project name -> terms
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetVocabularyParams {
+    #[serde(default)]
+    pub project: Option<String>,          // Project name (defaults to "default")
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SetVocabularyParams {
+    #[serde(default)]
+    pub project: Option<String>,          // Project name (defaults to "default")
+    pub terms: Vec<String>,               // Names, product terms, hashtags - replaces the stored list
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VocabularyResult {
+    pub project: String,                  // Project name the terms belong to
+    pub terms: Vec<String>,               // Stored terms, in the order they were given
+}
+
+// Script alignment types
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignTranscriptParams {
+    pub input: String,                            // Path to the audio or video file
+    pub script: String,                           // The exact text that is spoken, as it should appear
+    pub model: Option<String>,                    // Whisper model used for word timings
+    pub language: Option<String>,                 // Language hint, or "auto" to detect
+    #[serde(default)]
+    pub split_by_words: bool,                     // One segment per word instead of per sentence/line
+    pub api_key: Option<String>,                  // OpenAI API key
+    #[serde(default)]
+    pub project: Option<String>,                  // Project whose vocabulary is added to the prompt
+    #[serde(default)]
+    pub backend: Option<String>,                  // Force one backend by name
+    #[serde(default)]
+    pub backend_preference: Option<Vec<String>>,  // Ordered backends to try
+    #[serde(default)]
+    pub api_endpoint: Option<ApiEndpointConfig>,  // OpenAI-compatible server settings
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AlignTranscriptResult {
+    pub segments: Vec<CaptionSegment>,            // Script text with timings from the audio
+    pub full_text: String,                        // The script, whitespace-normalized
+    pub duration: Option<f64>,                    // Total audio duration
+    pub language: Option<String>,                 // Detected (or forced) language as ISO 639-1 code
+    pub matched_words: usize,                     // Script words timed directly from recognized speech
+    pub total_words: usize,                       // Script words in total (the rest are interpolated)
+}
+
+// Text-based editing types
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyTextEditsParams {
+    pub input_video: String,                      // Path to the source video
+    pub segments: Vec<CaptionSegment>,            // The transcription the edits refer to
+    pub deleted: Vec<WordRange>,                  // Word ranges removed in the editor
+    pub out: Option<String>,                      // Output path (default: <input>_edited.mp4)
+    pub crossfade_ms: Option<u64>,                // Audio crossfade at each cut (default: 30, 0 = hard cut)
+}
+
+/// Words `start..end` (end exclusive) of a transcription, counted across segments:
+/// every `WordSpan` is one word, and so is a segment without word timings
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct WordRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyTextEditsResult {
+    pub video: String,                            // Path to the edited video
+    pub segments: Vec<CaptionSegment>,            // Remaining captions, timed for the edited video
+    pub full_text: String,                        // Text of the remaining captions
+    pub duration: Option<f64>,                    // Length of the edited video in seconds
+    pub cuts: Vec<TimeRange>,                     // Edit decision list: source ranges that were removed
+}
@@ -1,3 +1,4 @@
+use crate::filtergraph::{Filter, FilterChain};
 use crate::rpc::RpcEvent;
 use crate::whisper::{find_ffmpeg_binary, find_ffprobe_binary};
 use serde::{Deserialize, Serialize};
@@ -74,19 +75,6 @@ fn get_fonts_dir() -> Option<std::path::PathBuf> {
     None
 }
 
-/// Properly escape subtitle file paths for FFmpeg subtitle filter
-/// Handles Windows paths with drive letters and special characters
-pub fn escape_subtitle_path(path: &str) -> String {
-    // First, escape backslashes for Windows paths
-    let mut escaped = path.replace('\\', r"\\");
-
-    // Escape colons (Windows drive letters and FFmpeg filter separators)
-    escaped = escaped.replace(':', r"\:");
-
-    // Quote the entire path to handle spaces and other special characters
-    format!("'{}'", escaped)
-}
-
 #[derive(Clone, Copy)]
 pub enum TargetAR {
     AR9x16,
@@ -143,12 +131,12 @@ pub fn canvas_no_downscale(src_w: u32, src_h: u32, ar: TargetAR) -> (u32, u32) {
 
 /// Build a vf that keeps full source, centers it, and pads to target canvas.
 /// NOTE: No scaling! (video stays native pixels)
-fn vf_fit_pad_no_scale(src_w: u32, src_h: u32, ar: TargetAR, pad_color: &str) -> String {
+fn vf_fit_pad_no_scale(src_w: u32, src_h: u32, ar: TargetAR, pad_color: &str) -> Filter {
     let (out_w, out_h) = canvas_no_downscale(src_w, src_h, ar);
     // center the source inside the canvas
     let x = (out_w as i32 - src_w as i32) / 2;
     let y = (out_h as i32 - src_h as i32) / 2;
-    format!("pad={}:{}:{}:{}:{}", out_w, out_h, x.max(0), y.max(0), pad_color)
+    Filter::new("pad").arg(out_w).arg(out_h).arg(x.max(0)).arg(y.max(0)).arg(pad_color)
 }
 
 /// Optional scaling to a "platform standard" *after* padding.
@@ -190,52 +178,31 @@ pub fn build_fitpad_filter_with_format(
     subtitle_path: Option<&str>,
     encoder: HardwareEncoder
 ) -> String {
-    // Pre-calculate approximate capacity to avoid reallocations
-    let has_subtitles = subtitle_path.is_some();
-    let estimated_capacity = if has_subtitles {
-        200 + subtitle_path.map(|p| p.len()).unwrap_or(0) // ~200 chars + subtitle path length
-    } else {
-        120 // Without subtitles, much shorter
-    };
-
-    let mut result = String::with_capacity(estimated_capacity);
-    let mut first = true;
-
-    // Helper to add filter with comma separator
-    let mut add_filter = |filter: &str| {
-        if !first {
-            result.push(',');
-        }
-        result.push_str(filter);
-        first = false;
-    };
+    let mut chain = FilterChain::new();
 
     // Start with high-quality chroma for subtitle rendering (if needed)
-    if has_subtitles {
-        add_filter("format=yuv444p");
+    if subtitle_path.is_some() {
+        chain.push(Filter::new("format").arg("yuv444p"));
     }
 
     // High-quality scaling with letterboxing - BEFORE subtitles for final resolution text
-    add_filter(&format!(
-        "scale={}:{}:flags=lanczos:force_original_aspect_ratio=decrease",
-        target_w, target_h
-    ));
+    chain.push(Filter::new("scale")
+        .arg(target_w).arg(target_h)
+        .opt("flags", "lanczos")
+        .opt("force_original_aspect_ratio", "decrease"));
 
     // Pad to exact target dimensions with black bars - BEFORE subtitles
-    add_filter(&format!(
-        "pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
-        target_w, target_h
-    ));
+    chain.push(Filter::new("pad")
+        .arg(target_w).arg(target_h)
+        .arg("(ow-iw)/2").arg("(oh-ih)/2")
+        .arg("black"));
 
     if let Some(subtitle_path) = subtitle_path {
-        let escaped_path = escape_subtitle_path(subtitle_path);
-        // Get fonts directory (development or bundled)
-        if let Some(fonts_dir) = get_fonts_dir() {
-            add_filter(&format!("subtitles={}:fontsdir={}", escaped_path, fonts_dir.display()));
-        } else {
-            // No fontsdir specified - libass will use system fonts
-            add_filter(&format!("subtitles={}", escaped_path));
-        }
+        // Get fonts directory (development or bundled); without it libass uses system fonts
+        let fonts_dir = get_fonts_dir().map(|d| d.to_string_lossy().to_string());
+        chain.push(Filter::new("subtitles")
+            .opt("filename", subtitle_path)
+            .opt_if("fontsdir", fonts_dir));
     }
 
     // End with encoder-optimized format to avoid hidden conversions
@@ -244,9 +211,9 @@ pub fn build_fitpad_filter_with_format(
         HardwareEncoder::Nvenc => "nv12",        // NVENC also prefers NV12
         HardwareEncoder::Software => "yuv420p",  // libx264 broad compatibility
     };
-    add_filter(&format!("format={}", final_format));
+    chain.push(Filter::new("format").arg(final_format));
 
-    result
+    chain.to_string()
 }
 
 /// Determine the best audio codec and settings based on input analysis
@@ -346,10 +313,7 @@ pub async fn is_nvenc_available() -> bool {
 /// Check if whisper.cpp CLI is available (preferred method)
 pub async fn is_whisper_cpp_available() -> bool {
     // Use the new cross-platform whisper binary detection from whisper.rs
-    match crate::whisper::find_whisper_binary().await {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+    crate::whisper::find_whisper_binary().await.is_ok()
 }
 
 /// Check if FFmpeg has built-in Whisper support (requires FFmpeg 8.0+)
@@ -423,7 +387,7 @@ fn crf_to_videotoolbox_quality(crf: &str) -> String {
     if let Ok(crf_value) = crf.parse::<i32>() {
         // Invert and scale: CRF 18 -> ~75, CRF 23 -> ~60, CRF 28 -> ~45
         let quality = 100 - ((crf_value as f32 * 100.0) / 51.0) as i32;
-        quality.max(0).min(100).to_string()
+        quality.clamp(0, 100).to_string()
     } else {
         "65".to_string() // Fallback to medium-high quality
     }
@@ -564,8 +528,37 @@ pub struct ProbeResult {
     pub video: bool,              // True if file has video track
     pub audio_codec: Option<String>, // Audio codec name (e.g., "aac", "mp3", "pcm_s16le")
     pub audio_bitrate: Option<i32>,  // Audio bitrate in bits/sec (e.g., 128000)
+    #[serde(default)]
+    pub subtitle_tracks: Vec<SubtitleTrack>, // Embedded subtitle streams, in container order
+    #[serde(default)]
+    pub audio_tracks: Vec<AudioTrack>,       // Audio streams, in container order
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioTrack {
+    pub index: u32,               // Stream index in the container
+    pub codec: String,            // Codec name (e.g., "aac", "pcm_s24le")
+    pub channels: u32,            // Channel count (a stereo interview may carry one mic per channel)
+    pub language: Option<String>, // Language tag as stored in the container
+    pub title: Option<String>,    // Track title, if any (often the speaker's name)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtitleTrack {
+    pub index: u32,               // Stream index in the container (pass as `subtitleTrack`)
+    pub codec: String,            // Codec name (e.g., "mov_text", "subrip", "ass", "hdmv_pgs_subtitle")
+    pub language: Option<String>, // Language tag as stored in the container (e.g., "eng")
+    pub title: Option<String>,    // Track title, if any
+    pub default: bool,            // Marked as the default track
+    pub forced: bool,             // Marked as forced (foreign-dialogue-only) subtitles
+    pub text_based: bool,         // False for bitmap subtitles (PGS, VobSub), which can't be restyled
 }
 
+// Subtitle codecs ffmpeg can turn into text we can parse
+const TEXT_SUBTITLE_CODECS: &[&str] = &["mov_text", "subrip", "srt", "ass", "ssa", "webvtt", "text"];
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ExtractThumbnailParams {
@@ -619,14 +612,13 @@ pub async fn export_video(id: &str, p: ExportParams, mut emit: impl FnMut(RpcEve
     cmd.arg("-sws_flags").arg("lanczos+accurate_rnd+full_chroma_int");
 
     // Build video filter for high-quality export
-    let mut vf_parts = Vec::new();
+    let mut vf = FilterChain::new();
 
     // Handle video scaling/letterboxing with new high-quality approach
     if let (Some(width), Some(height)) = (p.width, p.height) {
         // exact dimensions specified - use old behavior for backward compatibility
-        let filter = format!("scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
-                           width, height, width, height);
-        vf_parts.push(filter);
+        vf.push(Filter::new("scale").arg(width).arg(height).opt("force_original_aspect_ratio", "decrease"))
+          .push(Filter::new("pad").arg(width).arg(height).arg("(ow-iw)/2").arg("(oh-ih)/2").arg("black"));
 
         emit(RpcEvent::Log {
             id: id.into(),
@@ -641,13 +633,11 @@ pub async fn export_video(id: &str, p: ExportParams, mut emit: impl FnMut(RpcEve
                 let src_h = orig_height as u32;
 
                 // Build pad filter (no scaling)
-                let pad_filter = vf_fit_pad_no_scale(src_w, src_h, target_ar, "black");
-                vf_parts.push(pad_filter);
+                vf.push(vf_fit_pad_no_scale(src_w, src_h, target_ar, "black"));
 
                 // Optional scaling to standard social media sizes
                 if let Some((std_w, std_h)) = maybe_scale_to_standard(target_ar, use_standard_sizes) {
-                    let scale_filter = format!("scale={}:{}:flags=lanczos", std_w, std_h);
-                    vf_parts.push(scale_filter);
+                    vf.push(Filter::new("scale").arg(std_w).arg(std_h).opt("flags", "lanczos"));
 
                     emit(RpcEvent::Log {
                         id: id.into(),
@@ -672,8 +662,8 @@ pub async fn export_video(id: &str, p: ExportParams, mut emit: impl FnMut(RpcEve
     }
 
     // Apply video filters if any
-    if !vf_parts.is_empty() {
-        cmd.arg("-vf").arg(vf_parts.join(","));
+    if !vf.is_empty() {
+        cmd.arg("-vf").arg(vf.to_string());
     }
 
     // High-quality encoding settings with cadence preservation
@@ -776,8 +766,8 @@ pub async fn export_video(id: &str, p: ExportParams, mut emit: impl FnMut(RpcEve
         fallback_cmd.arg("-sws_flags").arg("lanczos+accurate_rnd+full_chroma_int");
 
         // Reapply video filters
-        if !vf_parts.is_empty() {
-            fallback_cmd.arg("-vf").arg(vf_parts.join(","));
+        if !vf.is_empty() {
+            fallback_cmd.arg("-vf").arg(vf.to_string());
         }
 
         fallback_cmd.arg("-fps_mode").arg("passthrough").arg("-threads").arg("0");
@@ -904,6 +894,8 @@ pub async fn probe(id: &str, input: &str, mut emit: impl FnMut(RpcEvent)) -> any
     let mut video = false;
     let mut audio_codec = None;
     let mut audio_bitrate = None;
+    let mut subtitle_tracks = Vec::new();
+    let mut audio_tracks = Vec::new();
 
     // Analyze each stream in the file
     if let Some(arr) = v.get("streams").and_then(|s| s.as_array()) {
@@ -936,15 +928,38 @@ pub async fn probe(id: &str, input: &str, mut emit: impl FnMut(RpcEvent)) -> any
                         audio_bitrate = st.get("bit_rate")
                             .and_then(|x| x.as_str())
                             .and_then(|s| s.parse::<i32>().ok());
+
+                        let tag = |name: &str| st.get("tags").and_then(|t| t.get(name)).and_then(|x| x.as_str()).map(|s| s.to_string());
+                        audio_tracks.push(AudioTrack {
+                            index: st.get("index").and_then(|x| x.as_u64()).unwrap_or(0) as u32,
+                            codec: audio_codec.clone().unwrap_or_else(|| "unknown".to_string()),
+                            channels: st.get("channels").and_then(|x| x.as_u64()).unwrap_or(1) as u32,
+                            language: tag("language").filter(|l| l != "und"),
+                            title: tag("title"),
+                        });
+                    }
+                    "subtitle" => {
+                        let codec = st.get("codec_name").and_then(|x| x.as_str()).unwrap_or("unknown").to_string();
+                        let tag = |name: &str| st.get("tags").and_then(|t| t.get(name)).and_then(|x| x.as_str()).map(|s| s.to_string());
+                        let disposition = |name: &str| st.get("disposition").and_then(|d| d.get(name)).and_then(|x| x.as_i64()) == Some(1);
+                        subtitle_tracks.push(SubtitleTrack {
+                            index: st.get("index").and_then(|x| x.as_u64()).unwrap_or(0) as u32,
+                            text_based: TEXT_SUBTITLE_CODECS.contains(&codec.as_str()),
+                            codec,
+                            language: tag("language").filter(|l| l != "und"),
+                            title: tag("title"),
+                            default: disposition("default"),
+                            forced: disposition("forced"),
+                        });
                     }
-                    _ => {} // Ignore other stream types (subtitles, data, etc.)
+                    _ => {} // Ignore other stream types (data, attachments, etc.)
                 }
             }
         }
     }
 
     emit(RpcEvent::Progress { id: id.into(), status: "Probe complete".into(), progress: 1.0 });
-    Ok(ProbeResult { duration, width, height, fps, audio, video, audio_codec, audio_bitrate })
+    Ok(ProbeResult { duration, width, height, fps, audio, video, audio_codec, audio_bitrate, subtitle_tracks, audio_tracks })
 }
 
 
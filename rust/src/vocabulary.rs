@@ -0,0 +1,98 @@
+use crate::types::{GetVocabularyParams, SetVocabularyParams, VocabularyResult, VocabularyStore};
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+const DEFAULT_PROJECT: &str = "default";
+// whisper keeps at most ~224 prompt tokens; stay well under so the user's own prompt survives
+const MAX_VOCABULARY_PROMPT_CHARS: usize = 600;
+
+// Held across load-modify-save so concurrent setVocabulary calls don't drop each other's projects
+static STORE_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn get_vocabulary_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::whisper::get_app_data_dir()?.join("vocabulary.json"))
+}
+
+async fn load_store() -> anyhow::Result<VocabularyStore> {
+    let path = get_vocabulary_path()?;
+    if !path.exists() {
+        return Ok(VocabularyStore::default());
+    }
+    let content = fs::read_to_string(&path).await?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+async fn save_store(store: &VocabularyStore) -> anyhow::Result<()> {
+    let path = get_vocabulary_path()?;
+    // Write then rename so a crash never leaves a truncated vocabulary file
+    let tmp = crate::cache::unique_tmp_path(&path);
+    fs::write(&tmp, serde_json::to_string_pretty(store)?).await?;
+    fs::rename(&tmp, &path).await?;
+    Ok(())
+}
+
+fn project_name(project: Option<&str>) -> String {
+    project.map(str::trim).filter(|p| !p.is_empty()).unwrap_or(DEFAULT_PROJECT).to_string()
+}
+
+/// Trim, drop empties and case-insensitive duplicates, keep the user's order
+fn clean_terms(terms: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    terms.into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty() && seen.insert(t.to_lowercase()))
+        .collect()
+}
+
+pub async fn get_vocabulary(p: GetVocabularyParams) -> anyhow::Result<VocabularyResult> {
+    let project = project_name(p.project.as_deref());
+    let store = load_store().await?;
+    let terms = store.projects.get(&project).cloned().unwrap_or_default();
+    Ok(VocabularyResult { project, terms })
+}
+
+pub async fn set_vocabulary(p: SetVocabularyParams) -> anyhow::Result<VocabularyResult> {
+    let project = project_name(p.project.as_deref());
+    let terms = clean_terms(p.terms);
+
+    let _guard = STORE_LOCK.lock().await;
+    let mut store = load_store().await?;
+    if terms.is_empty() {
+        store.projects.remove(&project);
+    } else {
+        store.projects.insert(project.clone(), terms.clone());
+    }
+    save_store(&store).await?;
+
+    Ok(VocabularyResult { project, terms })
+}
+
+/// Combine the user's prompt with the project's vocabulary.
+/// Whisper treats the prompt as preceding transcript, so spelling out the terms
+/// biases it toward those exact spellings.
+pub fn build_prompt(prompt: Option<&str>, terms: &[String]) -> Option<String> {
+    let prompt = prompt.map(str::trim).filter(|p| !p.is_empty());
+
+    let mut glossary = String::new();
+    for term in terms {
+        if glossary.len() + term.len() + 2 > MAX_VOCABULARY_PROMPT_CHARS { break; }
+        if !glossary.is_empty() { glossary.push_str(", "); }
+        glossary.push_str(term);
+    }
+
+    match (prompt, glossary.is_empty()) {
+        (Some(prompt), true) => Some(prompt.to_string()),
+        (Some(prompt), false) => Some(format!("{} Glossary: {}.", prompt, glossary)),
+        (None, false) => Some(format!("Glossary: {}.", glossary)),
+        (None, true) => None,
+    }
+}
+
+/// The prompt a transcription should actually use for this project
+pub async fn prompt_with_vocabulary(prompt: Option<&str>, project: Option<&str>) -> anyhow::Result<Option<String>> {
+    let project = project_name(project);
+    let store = load_store().await?;
+    let terms = store.projects.get(&project).map(|t| t.as_slice()).unwrap_or_default();
+    Ok(build_prompt(prompt, terms))
+}
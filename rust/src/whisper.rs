@@ -1,19 +1,40 @@
-use crate::{types::{CaptionSegment, WhisperResponse, WhisperCacheEntry, WhisperCacheIndex, TranscribeSegmentsParams, TranscribeSegmentsResult, WhisperWord}};
+use crate::{types::{CaptionSegment, LowConfidenceWord, WhisperResponse, TranscribeSegmentsParams, TranscribeSegmentsResult, TranscriptionTask, WhisperWord, WordSpan}};
 use blake3;
 use tokio::fs;
 use tokio::process::Command as TokioCommand;
 use std::path::PathBuf;
 use std::process::Stdio;
+use crate::filtergraph::{Filter, FilterChain};
 use crate::rpc::RpcEvent;
-use crate::video::{is_ffmpeg_whisper_available, is_whisper_cpp_available};
 use regex::Regex;
 
+/// A backend ran fine but heard nothing to transcribe (music, silence, a muted mic).
+/// Callers that stitch several transcriptions treat this as a gap, unlike real failures.
+#[derive(Debug)]
+pub struct NoSpeech(pub &'static str);
+
+impl std::fmt::Display for NoSpeech {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No transcription text found in {} output", self.0)
+    }
+}
+
+impl std::error::Error for NoSpeech {}
+
+pub fn is_no_speech(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<NoSpeech>().is_some()
+}
+
 /// Transcribe audio using whisper.cpp CLI (preferred method)
+#[allow(clippy::too_many_arguments)]
 pub async fn transcribe_with_whisper_cpp(
     id: &str,
     audio_path: &str,
     model: Option<String>,
     language: Option<String>,
+    task: TranscriptionTask,
+    prompt: Option<String>,
+    diarize: bool,
     mut emit: impl FnMut(RpcEvent)
 ) -> anyhow::Result<WhisperResponse> {
     // Use requested model or default to tiny
@@ -65,7 +86,6 @@ pub async fn transcribe_with_whisper_cpp(
     };
     let mut cmd = TokioCommand::new(&whisper_binary);
     // DTW disabled - causes timestamp issues for some audio files
-    let dtw_preset: Option<&str> = None;
 
     cmd.arg("-m").arg(&model_path)
        .arg("--output-json-full")    // Full JSON output
@@ -81,6 +101,20 @@ pub async fn transcribe_with_whisper_cpp(
     if let Some(lang) = &language {
         cmd.arg("-l").arg(lang);
     }
+    if task == TranscriptionTask::Translate {
+        cmd.arg("--translate");
+    }
+    if let Some(prompt) = &prompt {
+        cmd.arg("--prompt").arg(prompt);
+    }
+    if diarize {
+        // tinydiarize models mark speaker turns; any other model can only split stereo channels
+        if actual_model.contains("tdrz") {
+            cmd.arg("--tinydiarize");
+        } else {
+            cmd.arg("--diarize");
+        }
+    }
 
     cmd.stdout(Stdio::piped())
        .stderr(Stdio::piped());
@@ -163,46 +197,13 @@ pub async fn transcribe_with_whisper_cpp(
 }
 
 /// Ensure whisper model exists with intelligent fallbacks
-async fn ensure_whisper_model(model: &str) -> anyhow::Result<(String, String)> {
-    // Define fallback chain: requested -> base -> tiny
-    let fallback_chain = match model {
-        "large" => vec!["large", "medium", "base", "tiny"],
-        "medium" => vec!["medium", "base", "tiny"],
-        "small" => vec!["small", "base", "tiny"],
-        "base" => vec!["base", "tiny"],
-        "tiny" => vec!["tiny"],
-        _ => vec!["base", "tiny"], // Unknown models fallback to base then tiny
-    };
-
-    for &fallback_model in &fallback_chain {
-        let model_filename = match fallback_model {
-            "tiny" => "ggml-tiny.bin",
-            "base" => "ggml-base.bin",
-            "small" => "ggml-small.bin",
-            "medium" => "ggml-medium.bin",
-            "large" => "ggml-large-v3.bin",
-            _ => continue,
-        };
-
-        // Check if model exists using the centralized models directory function
-        // This handles dev, production, and all platform-specific paths
-        if let Ok(models_dir) = get_models_dir() {
-            let model_path = models_dir.join(model_filename);
-            if model_path.exists() {
-                return Ok((model_path.to_string_lossy().to_string(), fallback_model.to_string()));
-            }
-        }
+pub(crate) async fn ensure_whisper_model(model: &str) -> anyhow::Result<(String, String)> {
+    // Requested model first, then smaller ones (see models::fallback_chain)
+    let fallback_chain = crate::models::fallback_chain(model);
 
-        // Also check system-wide locations as fallback
-        let system_paths = vec![
-            format!("/opt/homebrew/share/whisper-models/{}", model_filename),
-            format!("{}/.cache/whisper/{}", std::env::var("HOME").unwrap_or_default(), model_filename),
-        ];
-
-        for path in system_paths {
-            if std::path::Path::new(&path).exists() {
-                return Ok((path, fallback_model.to_string()));
-            }
+    for fallback_model in &fallback_chain {
+        if let Some(path) = crate::models::find_installed(fallback_model) {
+            return Ok((path.to_string_lossy().to_string(), fallback_model.clone()));
         }
     }
 
@@ -560,138 +561,23 @@ fn get_system_ffprobe_paths() -> Vec<String> {
     ]
 }
 
-/// Get download URL for whisper model
-fn get_model_download_url(model_filename: &str) -> String {
-    format!("https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}", model_filename)
-}
-
-/// Download whisper model from HuggingFace
-async fn download_whisper_model(url: &str, path: &str) -> anyhow::Result<()> {
-    use reqwest;
-    use tokio::io::AsyncWriteExt;
-
-    let response = reqwest::get(url).await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to download model: HTTP {}", response.status()));
-    }
-
-    let mut file = tokio::fs::File::create(path).await?;
-    let mut stream = response.bytes_stream();
-
-    use futures_util::StreamExt;
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        file.write_all(&chunk).await?;
-    }
-
-    file.flush().await?;
-    Ok(())
-}
-
-/// Public RPC method to download a whisper model with progress reporting
-pub async fn download_model_rpc(
-    id: &str,
-    params: crate::types::DownloadModelParams,
-    mut emit: impl FnMut(crate::rpc::RpcEvent)
-) -> anyhow::Result<crate::types::DownloadModelResult> {
-    use tokio::io::AsyncWriteExt;
-    use futures_util::StreamExt;
-
-    let model_filename = match params.model.as_str() {
-        "tiny" => "ggml-tiny.bin",
-        "base" => "ggml-base.bin",
-        "small" => "ggml-small.bin",
-        "medium" => "ggml-medium.bin",
-        "large" => "ggml-large-v3.bin",
-        _ => return Err(anyhow::anyhow!("Unknown model: {}. Supported: tiny, base, small, medium, large", params.model))
-    };
-
-    let url = get_model_download_url(model_filename);
-    let models_dir = get_models_dir()
-        .map_err(|e| anyhow::anyhow!("Cannot access models directory: {}. Please check app permissions.", e))?;
-    let output_path = models_dir.join(model_filename);
-
-    emit(crate::rpc::RpcEvent::Log {
-        id: id.into(),
-        message: format!("Models will be saved to: {}", models_dir.display())
-    });
-
-    emit(crate::rpc::RpcEvent::Log {
-        id: id.into(),
-        message: format!("Starting download of {} model from HuggingFace", params.model)
-    });
-
-    // Download with progress
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?;
-
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to download model: HTTP {}", response.status()));
-    }
-
-    let total_size = response.content_length().unwrap_or(0);
-
-    emit(crate::rpc::RpcEvent::Log {
-        id: id.into(),
-        message: format!("Downloading {} ({:.1} MB)...", model_filename, total_size as f64 / 1024.0 / 1024.0)
-    });
-
-    let mut file = tokio::fs::File::create(&output_path).await
-        .map_err(|e| anyhow::anyhow!("Cannot create model file at {}: {}. Check app permissions in System Settings > Privacy & Security.", output_path.display(), e))?;
-    let mut downloaded = 0u64;
-    let mut stream = response.bytes_stream();
-
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        file.write_all(&chunk).await?;
-        downloaded += chunk.len() as u64;
-
-        let progress = if total_size > 0 {
-            (downloaded as f64 / total_size as f64) as f32
-        } else {
-            0.0_f32
-        };
-
-        emit(crate::rpc::RpcEvent::Progress {
-            id: id.into(),
-            status: format!("Downloading {}...", params.model),
-            progress
-        });
-    }
-
-    file.flush().await?;
-
-    emit(crate::rpc::RpcEvent::Log {
-        id: id.into(),
-        message: format!("Successfully downloaded {} model to {}", params.model, output_path.display())
-    });
-
-    Ok(crate::types::DownloadModelResult {
-        model: params.model,
-        path: output_path.to_string_lossy().to_string(),
-        size: downloaded
-    })
-}
-
-/// Check if a model exists
-pub fn check_model_exists(model_name: &str) -> anyhow::Result<bool> {
-    let model_filename = match model_name {
-        "tiny" => "ggml-tiny.bin",
-        "base" => "ggml-base.bin",
-        "small" => "ggml-small.bin",
-        "medium" => "ggml-medium.bin",
-        "large" => "ggml-large-v3.bin",
-        _ => return Ok(false)
-    };
-
-    let models_dir = get_models_dir()
-        .map_err(|e| anyhow::anyhow!("Cannot access models directory: {}. Please check app permissions.", e))?;
-    let model_path = models_dir.join(model_filename);
-    Ok(model_path.exists())
+/// Per-user application data directory for persistent state (created on demand)
+pub(crate) fn get_app_data_dir() -> anyhow::Result<std::path::PathBuf> {
+    #[cfg(target_os = "macos")]
+    let base = std::env::var_os("HOME").map(|h| std::path::PathBuf::from(h).join("Library/Application Support/CapSlap"));
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("APPDATA").map(|a| std::path::PathBuf::from(a).join("CapSlap"));
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let base = std::env::var_os("HOME").map(|h| std::path::PathBuf::from(h).join(".local/share/capslap"));
+
+    let dir = base.unwrap_or_else(|| std::env::temp_dir().join("capslap"));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| anyhow::anyhow!("Failed to create app data directory at {}: {}", dir.display(), e))?;
+    Ok(dir)
 }
 
 /// Get the models directory path
-fn get_models_dir() -> anyhow::Result<std::path::PathBuf> {
+pub(crate) fn get_models_dir() -> anyhow::Result<std::path::PathBuf> {
     // Priority 1: Check if we're in development (project exists)
     let dev_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("models");
     if dev_path.exists() && dev_path.is_dir() {
@@ -756,6 +642,8 @@ fn parse_whisper_cpp_output(json_output: &str) -> anyhow::Result<WhisperResponse
     let mut segments = Vec::new();
     let mut words = Vec::new();
     let mut duration = 0.0f64;
+    // tinydiarize only flags turns, so speakers alternate between 0 and 1 (interview layout)
+    let mut turn_speaker = 0u32;
 
     if let Some(transcription) = json.get("transcription") {
         if let Some(array) = transcription.as_array() {
@@ -775,11 +663,25 @@ fn parse_whisper_cpp_output(json_output: &str) -> anyhow::Result<WhisperResponse
                         duration = end_sec;
                     }
 
+                    // --diarize reports "0"/"1" per stereo channel ("?" when unsure), -tdrz a turn flag
+                    let speaker = match (segment.get("speaker").and_then(|s| s.as_str()), segment.get("speaker_turn_next").and_then(|t| t.as_bool())) {
+                        (Some(channel), _) => channel.parse::<u32>().ok(),
+                        (None, Some(turn_next)) => {
+                            let current = turn_speaker;
+                            if turn_next { turn_speaker = 1 - turn_speaker; }
+                            Some(current)
+                        }
+                        (None, None) => None,
+                    };
+
                     segments.push(crate::types::WhisperSegment {
                         id: i as u32,
                         start: start_sec,
                         end: end_sec,
                         text: text.trim().to_string(),
+                        language: None,
+                        speaker,
+                        avg_logprob: None,
                     });
 
                     // TEMPORARILY DISABLE TOKEN PARSING - use only segment-level timing
@@ -832,6 +734,7 @@ fn parse_whisper_cpp_output(json_output: &str) -> anyhow::Result<WhisperResponse
                         .or_else(|| segment.get("words").and_then(|w| w.as_array()));
 
                     if let Some(tokens) = tokens_array {
+                        let mut segment_tokens = Vec::new();
                         for token in tokens {
                             // Try different JSON structures for token timing
                             let (token_text, token_start, token_end) = if let (Some(text), Some(start), Some(end)) = (
@@ -851,20 +754,15 @@ fn parse_whisper_cpp_output(json_output: &str) -> anyhow::Result<WhisperResponse
                                 continue; // Skip if we can't parse this token
                             };
 
-                            // Skip special tokens like [_BEG_] and empty/whitespace-only tokens
-                            let token_text_trimmed = token_text.trim();
-                            if !token_text_trimmed.is_empty()
-                                && !token_text_trimmed.starts_with('[')
-                                && !token_text_trimmed.ends_with(']')
-                                && token_start < token_end {
-
-                                words.push(crate::types::WhisperWord {
-                                    word: token_text_trimmed.to_string(),
-                                    start: token_start / 1000.0, // Convert ms to seconds
-                                    end: token_end / 1000.0,
-                                });
-                            }
+                            // Keep the raw text: the leading space marks a word boundary
+                            segment_tokens.push(crate::types::WhisperWord {
+                                word: token_text.to_string(),
+                                start: token_start / 1000.0, // Convert ms to seconds
+                                end: token_end / 1000.0,
+                                confidence: token.get("p").and_then(|p| p.as_f64()).map(|p| p as f32),
+                            });
                         }
+                        words.extend(merge_subword_tokens(&segment_tokens));
                     }
                 }
             }
@@ -874,12 +772,15 @@ fn parse_whisper_cpp_output(json_output: &str) -> anyhow::Result<WhisperResponse
     full_text = full_text.trim().to_string();
 
     if full_text.is_empty() {
-        return Err(anyhow::anyhow!("No transcription text found in whisper.cpp output"));
+        return Err(NoSpeech("whisper.cpp").into());
     }
 
+    let translate = json.get("params").and_then(|p| p.get("translate")).and_then(|t| t.as_bool()).unwrap_or(false);
+
     let response = WhisperResponse {
-        task: Some("transcribe".to_string()),
-        language: None,
+        task: Some(if translate { "translate" } else { "transcribe" }.to_string()),
+        // whisper.cpp reports the detected (or forced) language as a code, e.g. "en"
+        language: json.get("result").and_then(|r| r.get("language")).and_then(|l| l.as_str()).map(|s| s.to_string()),
         duration: Some(duration),
         text: full_text,
         segments: Some(segments.clone()),
@@ -889,6 +790,146 @@ fn parse_whisper_cpp_output(json_output: &str) -> anyhow::Result<WhisperResponse
     Ok(response)
 }
 
+// Lower of two optional confidences; a missing value doesn't count
+fn min_confidence(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// Punctuation that belongs to the preceding word even when whisper emits it with a leading space
+fn is_trailing_punctuation(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | '…' | ')' | ']' | '}' | '»' | '%' | '—' | '–'))
+}
+
+// Special tokens like [_BEG_], [_TT_150] or <|endoftext|>
+fn is_special_token(token: &str) -> bool {
+    (token.starts_with("[_") && token.ends_with(']')) || (token.starts_with("<|") && token.ends_with("|>"))
+}
+
+/// Merge whisper.cpp BPE subword tokens into whole words with combined timings.
+/// Tokens must keep their raw text: a leading space starts a new word, anything else
+/// continues the current one ("un" + "belie" + "vable"). Punctuation attaches to the
+/// preceding word, and a glued hyphen or apostrophe keeps the next piece in the same word.
+/// A word is only as certain as its least certain piece; punctuation doesn't count.
+pub(crate) fn merge_subword_tokens(tokens: &[WhisperWord]) -> Vec<WhisperWord> {
+    let mut out: Vec<WhisperWord> = Vec::new();
+    let mut cur: Option<WhisperWord> = None;
+    let mut glued_tail = false; // last piece of `cur` was appended without a leading space
+
+    for tok in tokens {
+        let trimmed = tok.word.trim();
+        if trimmed.is_empty() || is_special_token(trimmed) { continue; }
+
+        let has_space = tok.word.starts_with(char::is_whitespace);
+        let continues = match &cur {
+            None => false,
+            Some(w) => {
+                !has_space
+                    || is_trailing_punctuation(trimmed)
+                    || (glued_tail && (w.word.ends_with('-') || w.word.ends_with('\'')))
+            }
+        };
+
+        if continues {
+            let w = cur.as_mut().unwrap();
+            w.word.push_str(trimmed);
+            // zero-length punctuation tokens shouldn't drag the word's timing around
+            if tok.end > tok.start {
+                w.start = w.start.min(tok.start);
+                w.end = w.end.max(tok.end);
+            }
+            if !is_trailing_punctuation(trimmed) {
+                w.confidence = min_confidence(w.confidence, tok.confidence);
+            }
+            glued_tail = !has_space;
+        } else {
+            if let Some(w) = cur.take() { out.push(w); }
+            cur = Some(WhisperWord { word: trimmed.to_string(), start: tok.start, end: tok.end, confidence: tok.confidence });
+            glued_tail = false;
+        }
+    }
+    if let Some(w) = cur { out.push(w); }
+
+    out.retain(|w| w.start < w.end);
+    out
+}
+
+// Languages whisper knows, as (ISO 639-1 code, English name).
+// whisper-server and the OpenAI API report the name, whisper.cpp reports the code.
+const WHISPER_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "english"), ("zh", "chinese"), ("de", "german"), ("es", "spanish"), ("ru", "russian"),
+    ("ko", "korean"), ("fr", "french"), ("ja", "japanese"), ("pt", "portuguese"), ("tr", "turkish"),
+    ("pl", "polish"), ("ca", "catalan"), ("nl", "dutch"), ("ar", "arabic"), ("sv", "swedish"),
+    ("it", "italian"), ("id", "indonesian"), ("hi", "hindi"), ("fi", "finnish"), ("vi", "vietnamese"),
+    ("he", "hebrew"), ("uk", "ukrainian"), ("el", "greek"), ("ms", "malay"), ("cs", "czech"),
+    ("ro", "romanian"), ("da", "danish"), ("hu", "hungarian"), ("ta", "tamil"), ("no", "norwegian"),
+    ("th", "thai"), ("ur", "urdu"), ("hr", "croatian"), ("bg", "bulgarian"), ("lt", "lithuanian"),
+    ("la", "latin"), ("mi", "maori"), ("ml", "malayalam"), ("cy", "welsh"), ("sk", "slovak"),
+    ("te", "telugu"), ("fa", "persian"), ("lv", "latvian"), ("bn", "bengali"), ("sr", "serbian"),
+    ("az", "azerbaijani"), ("sl", "slovenian"), ("kn", "kannada"), ("et", "estonian"), ("mk", "macedonian"),
+    ("br", "breton"), ("eu", "basque"), ("is", "icelandic"), ("hy", "armenian"), ("ne", "nepali"),
+    ("mn", "mongolian"), ("bs", "bosnian"), ("kk", "kazakh"), ("sq", "albanian"), ("sw", "swahili"),
+    ("gl", "galician"), ("mr", "marathi"), ("pa", "punjabi"), ("si", "sinhala"), ("km", "khmer"),
+    ("sn", "shona"), ("yo", "yoruba"), ("so", "somali"), ("af", "afrikaans"), ("oc", "occitan"),
+    ("ka", "georgian"), ("be", "belarusian"), ("tg", "tajik"), ("sd", "sindhi"), ("gu", "gujarati"),
+    ("am", "amharic"), ("yi", "yiddish"), ("lo", "lao"), ("uz", "uzbek"), ("fo", "faroese"),
+    ("ht", "haitian creole"), ("ps", "pashto"), ("tk", "turkmen"), ("nn", "nynorsk"), ("mt", "maltese"),
+    ("sa", "sanskrit"), ("lb", "luxembourgish"), ("my", "myanmar"), ("bo", "tibetan"), ("tl", "tagalog"),
+    ("mg", "malagasy"), ("as", "assamese"), ("tt", "tatar"), ("haw", "hawaiian"), ("ln", "lingala"),
+    ("ha", "hausa"), ("ba", "bashkir"), ("jw", "javanese"), ("su", "sundanese"), ("yue", "cantonese"),
+];
+
+/// True when the caller asked whisper to detect the language itself
+pub fn is_auto_language(language: Option<&str>) -> bool {
+    language.map(|l| l.trim().eq_ignore_ascii_case("auto")).unwrap_or(false)
+}
+
+/// Normalize a language code or English language name ("English", "en") to the ISO 639-1 code
+pub fn normalize_language_code(language: &str) -> Option<String> {
+    let l = language.trim().to_lowercase();
+    if l.is_empty() || l == "auto" {
+        return None;
+    }
+    WHISPER_LANGUAGES.iter()
+        .find(|(code, name)| *code == l || *name == l)
+        .map(|(code, _)| code.to_string())
+}
+
+/// Settle on one normalized language for the response and tag each segment with it.
+/// Falls back to the caller's hint when the backend didn't report anything.
+fn finalize_detected_language(response: &mut WhisperResponse, hint: Option<&str>) {
+    let detected = response.language.as_deref().and_then(normalize_language_code)
+        .or_else(|| hint.and_then(normalize_language_code));
+
+    if let Some(segments) = response.segments.as_mut() {
+        for seg in segments {
+            seg.language = seg.language.as_deref().and_then(normalize_language_code)
+                .or_else(|| detected.clone());
+        }
+    }
+    response.language = detected;
+}
+
+/// Digit grouping and decimal separators used when merging spoken numbers
+#[derive(Debug, Clone, Copy)]
+struct NumberFormat {
+    thousands: char,
+    decimal: char,
+}
+
+fn number_format_for(language: Option<&str>) -> NumberFormat {
+    match language {
+        // Narrow no-break space keeps the number in one caption token
+        Some("fr") | Some("ru") | Some("uk") | Some("pl") | Some("cs") | Some("sv") | Some("fi") | Some("no") =>
+            NumberFormat { thousands: '\u{202F}', decimal: ',' },
+        Some("de") | Some("es") | Some("pt") | Some("it") | Some("nl") | Some("tr") | Some("id") | Some("da") =>
+            NumberFormat { thousands: '.', decimal: ',' },
+        _ => NumberFormat { thousands: ',', decimal: '.' },
+    }
+}
+
 /// Transcribe audio using local FFmpeg Whisper (requires FFmpeg 8.0+)
 pub async fn transcribe_with_ffmpeg_whisper(
     id: &str,
@@ -911,13 +952,12 @@ pub async fn transcribe_with_ffmpeg_whisper(
        .arg("-af");
 
     // Build whisper filter arguments
-    let mut whisper_filter = format!("whisper=model={}:print_text=1", whisper_model);
+    let whisper_filter = FilterChain::new().then(Filter::new("whisper")
+        .opt("model", &whisper_model)
+        .opt("print_text", 1)
+        .opt_if("language", language.as_ref()));
 
-    if let Some(lang) = &language {
-        whisper_filter.push_str(&format!(":language={}", lang));
-    }
-
-    cmd.arg(whisper_filter)
+    cmd.arg(whisper_filter.to_string())
        .arg("-f").arg("null")
        .arg("-")
        .stdout(Stdio::piped())
@@ -996,6 +1036,9 @@ fn parse_ffmpeg_whisper_output(stderr: &str) -> anyhow::Result<WhisperResponse>
                     start,
                     end,
                     text: text.clone(),
+                    language: None,
+                    speaker: None,
+                    avg_logprob: None,
                 });
             }
         }
@@ -1005,12 +1048,16 @@ fn parse_ffmpeg_whisper_output(stderr: &str) -> anyhow::Result<WhisperResponse>
     full_text = full_text.trim().to_string();
 
     if full_text.is_empty() {
-        return Err(anyhow::anyhow!("No transcription text found in FFmpeg output"));
+        return Err(NoSpeech("FFmpeg").into());
     }
 
+    // whisper.cpp logs "auto-detected language: de (p = 0.97)" when running with language=auto
+    let lang_re = Regex::new(r"auto-detected language:\s*([a-z]{2,3})")?;
+    let language = lang_re.captures(stderr).map(|c| c[1].to_string());
+
     Ok(WhisperResponse {
         task: Some("transcribe".to_string()),
-        language: None, // FFmpeg doesn't always report detected language
+        language, // Only reported when auto-detecting; the hint fills in otherwise
         duration: Some(duration),
         text: full_text,
         segments: Some(segments),
@@ -1018,6 +1065,26 @@ fn parse_ffmpeg_whisper_output(stderr: &str) -> anyhow::Result<WhisperResponse>
     })
 }
 
+/// JSON export path: in the temp directory if given, otherwise next to the video (or audio).
+/// Translations get their own file so a bilingual run keeps both.
+fn transcription_json_path(id: &str, params: &TranscribeSegmentsParams, temp_dir: Option<&std::path::PathBuf>) -> String {
+    let translated = params.task == TranscriptionTask::Translate;
+    if let Some(temp_dir) = temp_dir {
+        let prefix = if translated { "translation" } else { "transcription" };
+        let json_filename = format!("{}_{}.json", prefix, id);
+        temp_dir.join(json_filename).to_string_lossy().to_string()
+    } else {
+        let base_path = if let Some(ref video_file) = params.video_file {
+            std::path::Path::new(video_file)
+        } else {
+            std::path::Path::new(&params.audio)
+        };
+        let mut json_path = base_path.to_path_buf();
+        json_path.set_extension(if translated { "en.json" } else { "json" });
+        json_path.to_string_lossy().to_string()
+    }
+}
+
 /// Helper function to create transcription result with JSON file generation
 async fn create_transcription_result(
     id: &str,
@@ -1028,29 +1095,20 @@ async fn create_transcription_result(
 ) -> anyhow::Result<TranscribeSegmentsResult> {
     use tokio::fs;
 
-    // Generate JSON file path based on temp directory (or video file location if no temp dir)
-    let json_path = if let Some(temp_dir) = temp_dir {
-        let json_filename = format!("transcription_{}.json", id);
-        temp_dir.join(json_filename).to_string_lossy().to_string()
-    } else {
-        let base_path = if let Some(ref video_file) = params.video_file {
-            std::path::Path::new(video_file)
-        } else {
-            std::path::Path::new(&params.audio)
-        };
-        let mut json_path = base_path.to_path_buf();
-        json_path.set_extension("json");
-        json_path.to_string_lossy().to_string()
-    };
+    let json_path = transcription_json_path(id, params, temp_dir);
+    let low_confidence_words = low_confidence_words(whisper_response);
 
     // Create JSON export data
     let json_data = serde_json::json!({
         "segments": segments,
+        "lowConfidenceWords": low_confidence_words,
         "fullText": whisper_response.text,
         "duration": whisper_response.duration,
         "splitByWords": params.split_by_words,
+        "task": params.task,
         "model": params.model.clone().unwrap_or_else(|| "whisper-1".to_string()),
-        "language": params.language.clone(),
+        "language": whisper_response.language.clone(),
+        "requestedLanguage": params.language.clone(),
         "generatedAt": std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -1065,82 +1123,57 @@ async fn create_transcription_result(
         full_text: whisper_response.text.clone(),
         duration: whisper_response.duration,
         json_file: json_path,
+        language: whisper_response.language.clone(),
+        low_confidence_words,
     })
 }
 
-pub async fn transcribe_segments(id: &str, p: TranscribeSegmentsParams, emit: impl FnMut(RpcEvent)) -> anyhow::Result<TranscribeSegmentsResult> {
+pub async fn transcribe_segments(id: &str, p: TranscribeSegmentsParams, emit: impl FnMut(RpcEvent) + Send) -> anyhow::Result<TranscribeSegmentsResult> {
     transcribe_segments_with_temp(id, p, None, emit).await
 }
 
-pub async fn transcribe_segments_with_temp(id: &str, p: TranscribeSegmentsParams, temp_dir: Option<&std::path::PathBuf>, mut emit: impl FnMut(RpcEvent)) -> anyhow::Result<TranscribeSegmentsResult> {
-    use reqwest::multipart;
-    use mime_guess::MimeGuess;
-    use tokio::fs;
-
-    // QUICK SWITCH: Set to false to force OpenAI API, true for local whisper
-    const USE_LOCAL_WHISPER: bool = true;
-
-    // Check cache first
-    if let Ok(Some(cached_response)) = get_cached_whisper_response(&p.audio, &p).await {
-        let segments = whisper_to_caption_segments(&cached_response, p.split_by_words);
-
-        // generate JSON file path for cached response too
-        let json_path = if let Some(temp_dir) = temp_dir {
-            let json_filename = format!("transcription_{}.json", id);
-            temp_dir.join(json_filename).to_string_lossy().to_string()
-        } else {
-            let base_path = if let Some(ref video_file) = p.video_file {
-                std::path::Path::new(video_file)
-            } else {
-                std::path::Path::new(&p.audio)
-            };
-            let mut json_path = base_path.to_path_buf();
-            json_path.set_extension("json");
-            json_path.to_string_lossy().to_string()
-        };
-
-        // save JSON file for cached response as well
-        let json_data = serde_json::json!({
-            "segments": segments,
-            "fullText": cached_response.text,
-            "duration": cached_response.duration,
-            "splitByWords": p.split_by_words,
-            "model": p.model.clone().unwrap_or_else(|| "whisper-1".to_string()),
-            "language": p.language.clone(),
-            "generatedAt": std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        });
+pub async fn transcribe_segments_with_temp(id: &str, p: TranscribeSegmentsParams, temp_dir: Option<&std::path::PathBuf>, mut emit: impl FnMut(RpcEvent) + Send) -> anyhow::Result<TranscribeSegmentsResult> {
+    // Fold the project's vocabulary into the prompt (this also keys the cache)
+    let mut p = p;
+    match crate::vocabulary::prompt_with_vocabulary(p.prompt.as_deref(), p.project.as_deref()).await {
+        Ok(prompt) => p.prompt = prompt,
+        Err(e) => emit(RpcEvent::Log { id: id.into(), message: format!("Failed to load vocabulary: {}", e) }),
+    }
 
-        let json_content = serde_json::to_string_pretty(&json_data)?;
-        fs::write(&json_path, json_content).await?;
+    // Cache lookups need the audio content hash; hashed once, streaming
+    let audio_hash = {
+        let audio = p.audio.clone();
+        tokio::task::spawn_blocking(move || hash_file(&audio)).await?.ok()
+    };
 
-        return Ok(TranscribeSegmentsResult {
-            segments,
-            full_text: cached_response.text,
-            duration: cached_response.duration,
-            json_file: json_path,
-        });
-    }
+    // Try each eligible backend in preference order until one succeeds
+    let candidates = crate::backend::select_backends(&p)?;
+    let mut failures = Vec::new();
+    let mut no_speech = None;
+    let mut failed = false;
 
-    // Check if user explicitly selected OpenAI API (whisper-1)
-    let use_openai_directly = p.model.as_ref().map(|m| m == "whisper-1").unwrap_or(false);
+    for backend in candidates {
+        if !backend.is_available(&p).await {
+            failures.push(format!("{}: not available", backend.name()));
+            continue;
+        }
 
-    // Try local whisper.cpp first if available (unless whisper-1 is explicitly selected)
-    if !use_openai_directly && USE_LOCAL_WHISPER && is_whisper_cpp_available().await {
-        emit(RpcEvent::Log {
-            id: id.into(),
-            message: "whisper.cpp detected, attempting local transcription...".into()
-        });
+        let model = backend.resolved_model(&p).await;
+        let cache_key = audio_hash.as_deref().map(|hash| crate::cache::CacheKey::new(hash, &p, backend.name(), &model));
+        if let Some(key) = &cache_key {
+            if let Ok(Some(mut cached_response)) = crate::cache::get_cached_whisper_response(key).await {
+                emit(RpcEvent::Log { id: id.into(), message: format!("Using cached transcription ({} backend, model {})", backend.name(), model) });
+                finalize_detected_language(&mut cached_response, p.language.as_deref());
+                let segments = whisper_to_caption_segments(&cached_response, p.split_by_words);
+                return create_transcription_result(id, &segments, &cached_response, &p, temp_dir).await;
+            }
+        }
 
-        match transcribe_with_whisper_cpp(id, &p.audio, p.model.clone(), p.language.clone(), &mut emit).await {
-            Ok(whisper_response) => {
-                emit(RpcEvent::Log {
-                    id: id.into(),
-                    message: "Local whisper.cpp transcription successful".into()
-                });
+        emit(RpcEvent::Log { id: id.into(), message: format!("Transcribing with {} backend", backend.name()) });
 
+        match crate::backend::run_backend(id, backend, &p, temp_dir, &mut emit).await {
+            Ok(mut whisper_response) => {
+                finalize_detected_language(&mut whisper_response, p.language.as_deref());
                 let segments = whisper_to_caption_segments(&whisper_response, p.split_by_words);
 
                 emit(RpcEvent::Log {
@@ -1150,119 +1183,241 @@ pub async fn transcribe_segments_with_temp(id: &str, p: TranscribeSegmentsParams
                 });
 
                 // Save to cache
-                if let Err(e) = save_cached_whisper_response(&p.audio, &p, &whisper_response).await {
-                    emit(RpcEvent::Log { id: id.into(), message: format!("Failed to cache local transcription: {}", e) });
+                if let Some(key) = &cache_key {
+                    if let Err(e) = crate::cache::save_cached_whisper_response(key, &whisper_response).await {
+                        emit(RpcEvent::Log { id: id.into(), message: format!("Failed to cache transcription: {}", e) });
+                    }
                 }
 
                 // Generate JSON file and return result
                 return create_transcription_result(id, &segments, &whisper_response, &p, temp_dir).await;
             }
             Err(e) => {
-                let error_msg = if e.to_string().contains("No whisper models found") {
-                    format!("No local whisper models available, falling back to OpenAI API. ({})", e)
-                } else {
-                    format!("Local whisper.cpp failed: {}, falling back to OpenAI API", e)
-                };
-
                 emit(RpcEvent::Log {
                     id: id.into(),
-                    message: error_msg
+                    message: format!("{} backend failed: {}, trying next backend", backend.name(), e)
                 });
+                failures.push(format!("{}: {}", backend.name(), e));
+                if is_no_speech(&e) {
+                    no_speech.get_or_insert(e);
+                } else {
+                    failed = true;
+                }
             }
         }
     }
 
-    // Try local FFmpeg Whisper as fallback (unless whisper-1 is explicitly selected)
-    if !use_openai_directly && USE_LOCAL_WHISPER && is_ffmpeg_whisper_available().await {
-        emit(RpcEvent::Log {
-            id: id.into(),
-            message: "FFmpeg Whisper detected, attempting local transcription...".into()
-        });
+    // Every backend that ran heard nothing: report silence, not a failure
+    if let (Some(e), false) = (no_speech, failed) {
+        return Err(e);
+    }
+    Err(anyhow::anyhow!("No transcription backend succeeded ({})", failures.join("; ")))
+}
 
-        match transcribe_with_ffmpeg_whisper(id, &p.audio, p.model.clone(), p.language.clone(), &mut emit).await {
-            Ok(whisper_response) => {
-                emit(RpcEvent::Log {
-                    id: id.into(),
-                    message: "Local FFmpeg Whisper transcription successful".into()
-                });
+// ---- OpenAI-compatible API tuning ----
+const DEFAULT_API_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_API_MODEL: &str = "whisper-1";
+const DEFAULT_MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024; // OpenAI's documented limit
+const API_MAX_ATTEMPTS: u32 = 4;
+const API_INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
 
-                let segments = whisper_to_caption_segments(&whisper_response, p.split_by_words);
+/// Model name sent to the OpenAI-compatible API
+pub(crate) fn api_model(p: &TranscribeSegmentsParams) -> String {
+    p.api_endpoint.as_ref().and_then(|e| e.model.clone()).unwrap_or_else(|| DEFAULT_API_MODEL.to_string())
+}
 
-                // Save to cache
-                if let Err(e) = save_cached_whisper_response(&p.audio, &p, &whisper_response).await {
-                    emit(RpcEvent::Log { id: id.into(), message: format!("Failed to cache local transcription: {}", e) });
-                }
+/// Transcribe through the OpenAI audio API or any OpenAI-compatible server
+/// (faster-whisper-server, LocalAI, proxies). Audio over the upload limit is
+/// recompressed first and, if still too big, split at silences and sent in pieces.
+pub async fn transcribe_with_openai(
+    id: &str,
+    p: &TranscribeSegmentsParams,
+    mut emit: impl FnMut(RpcEvent)
+) -> anyhow::Result<WhisperResponse> {
+    let endpoint = p.api_endpoint.clone().unwrap_or_default();
+    let limit = endpoint.max_upload_bytes.unwrap_or(DEFAULT_MAX_UPLOAD_BYTES);
 
-                // Generate JSON file and return result
-                return create_transcription_result(id, &segments, &whisper_response, &p, temp_dir).await;
-            }
-            Err(e) => {
-                emit(RpcEvent::Log {
-                    id: id.into(),
-                    message: format!("Local FFmpeg Whisper failed: {}, falling back to API", e)
-                });
-            }
-        }
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Sending audio to {} ({}, model {})",
+            endpoint.base_url.as_deref().unwrap_or(DEFAULT_API_BASE_URL), p.task.as_str(),
+            endpoint.model.as_deref().unwrap_or(DEFAULT_API_MODEL))
+    });
+
+    let size = fs::metadata(&p.audio).await?.len();
+    if size <= limit {
+        return post_audio_with_retries(id, p, &endpoint, &p.audio, &mut emit).await;
     }
 
+    // Over the limit: low-bitrate mono speech is usually small enough
+    let compressed = format!("{}.upload.mp3", p.audio);
+    encode_upload_audio(&p.audio, &compressed, None).await?;
+    let compressed_size = fs::metadata(&compressed).await?.len();
     emit(RpcEvent::Log {
         id: id.into(),
-        message: "No local Whisper available, using OpenAI API".into()
+        message: format!("Audio is {} bytes (limit {}), recompressed to {} bytes", size, limit, compressed_size)
     });
 
-    // Fallback to OpenAI API
-    let api_key = p.api_key.as_ref().ok_or_else(|| anyhow::anyhow!("OpenAI API key not provided"))?;
-    // Always use whisper-1 for OpenAI API (local model names like "tiny" are not valid for the API)
-    let model = "whisper-1".to_string();
+    let result = if compressed_size <= limit {
+        post_audio_with_retries(id, p, &endpoint, &compressed, &mut emit).await
+    } else {
+        upload_in_pieces(id, p, &endpoint, compressed_size, limit, &mut emit).await
+    };
+    let _ = fs::remove_file(&compressed).await;
+    result
+}
+
+/// Split the audio at silences into pieces that each fit the upload limit and stitch the results
+async fn upload_in_pieces(
+    id: &str,
+    p: &TranscribeSegmentsParams,
+    endpoint: &crate::types::ApiEndpointConfig,
+    compressed_size: u64,
+    limit: u64,
+    emit: &mut impl FnMut(RpcEvent)
+) -> anyhow::Result<WhisperResponse> {
+    let duration = crate::video::probe(id, &p.audio, |_| {}).await?.duration
+        .ok_or_else(|| anyhow::anyhow!("Cannot split audio without a known duration"))?;
 
-    let bytes = fs::read(&p.audio).await?;
-    let filename = std::path::Path::new(&p.audio).file_name().unwrap_or_default().to_string_lossy().to_string();
-    let mime = MimeGuess::from_path(&p.audio).first_or_octet_stream();
+    // Aim for 90% of the limit per piece to leave room for bitrate variance
+    let pieces = (compressed_size as f64 / (limit as f64 * 0.9)).ceil().max(2.0);
+    let silences = crate::chunking::detect_silences(&p.audio).await.unwrap_or_default();
+    let chunks = crate::chunking::plan_chunks_max(duration, &silences, duration / pieces);
 
-    // build form for verbose_json with appropriate timestamp granularities
-    let mut form = multipart::Form::new()
-        .text("model", model.clone())
-        .part("file", multipart::Part::bytes(bytes.clone()).file_name(filename.clone()).mime_str(mime.as_ref()).unwrap())
-        .text("response_format", "verbose_json".to_string());
+    emit(RpcEvent::Log { id: id.into(), message: format!("Uploading audio in {} pieces", chunks.len()) });
 
-    if let Some(lang) = &p.language {
-        form = form.text("language", lang.clone());
-    }
-    if let Some(prompt) = &p.prompt {
-        form = form.text("prompt", prompt.clone());
+    let mut parts = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let piece_path = format!("{}.part{:03}.mp3", p.audio, chunk.index);
+        encode_upload_audio(&p.audio, &piece_path, Some((chunk.start, chunk.end))).await?;
+        let result = post_audio_with_retries(id, p, endpoint, &piece_path, emit).await;
+        let _ = fs::remove_file(&piece_path).await;
+        parts.push((chunk, result?));
     }
 
-    // set timestamp granularities based on split_by_words preference
-    if p.split_by_words {
-        form = form.text("timestamp_granularities[]", "word".to_string());
-    } else {
-        form = form.text("timestamp_granularities[]", "segment".to_string());
+    Ok(crate::chunking::stitch_responses(parts, duration))
+}
+
+/// Re-encode (a range of) the audio as 16 kHz mono 32 kbps MP3, about 14 MB per hour
+async fn encode_upload_audio(input: &str, output: &str, range: Option<(f64, f64)>) -> anyhow::Result<()> {
+    let ffmpeg_path = find_ffmpeg_binary().await?;
+    let mut cmd = TokioCommand::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error");
+    if let Some((start, end)) = range {
+        cmd.arg("-ss").arg(format!("{:.3}", start))
+           .arg("-t").arg(format!("{:.3}", end - start));
+    }
+    let status = cmd
+        .arg("-i").arg(input)
+        .arg("-vn")
+        .arg("-ac").arg("1")
+        .arg("-ar").arg("16000")
+        .arg("-c:a").arg("libmp3lame")
+        .arg("-b:a").arg("32k")
+        .arg(output)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("ffmpeg failed to prepare audio for upload: {}", input));
     }
+    Ok(())
+}
+
+/// POST one audio file, retrying with exponential backoff on 429, 5xx and transport errors
+async fn post_audio_with_retries(
+    id: &str,
+    p: &TranscribeSegmentsParams,
+    endpoint: &crate::types::ApiEndpointConfig,
+    audio_path: &str,
+    emit: &mut impl FnMut(RpcEvent)
+) -> anyhow::Result<WhisperResponse> {
+    use reqwest::multipart;
+    use mime_guess::MimeGuess;
+
+    let base_url = endpoint.base_url.as_deref().unwrap_or(DEFAULT_API_BASE_URL).trim_end_matches('/');
+    let model = endpoint.model.clone().unwrap_or_else(|| DEFAULT_API_MODEL.to_string());
+    // Translations take neither a language nor timestamp granularities (segment timing only)
+    let url = match p.task {
+        TranscriptionTask::Translate => format!("{}/audio/translations", base_url),
+        TranscriptionTask::Transcribe => format!("{}/audio/transcriptions", base_url),
+    };
+
+    let bytes = fs::read(audio_path).await?;
+    let filename = std::path::Path::new(audio_path).file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mime = MimeGuess::from_path(audio_path).first_or_octet_stream();
 
     let client = reqwest::Client::builder().user_agent("core/1.0.0").build()?;
+    let mut backoff = API_INITIAL_BACKOFF;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        // build form for verbose_json with appropriate timestamp granularities (forms can't be reused)
+        let mut form = multipart::Form::new()
+            .text("model", model.clone())
+            .part("file", multipart::Part::bytes(bytes.clone()).file_name(filename.clone()).mime_str(mime.as_ref())?)
+            .text("response_format", "verbose_json".to_string());
+
+        if let Some(prompt) = &p.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        if p.task == TranscriptionTask::Transcribe {
+            // The API auto-detects when no language is sent
+            if let Some(lang) = p.language.as_ref().filter(|l| !is_auto_language(Some(l))) {
+                form = form.text("language", lang.clone());
+            }
 
-    let resp = client.post("https://api.openai.com/v1/audio/transcriptions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .multipart(form)
-        .send()
-        .await?;
+            // set timestamp granularities based on split_by_words preference
+            if p.split_by_words {
+                form = form.text("timestamp_granularities[]", "word".to_string());
+            } else {
+                form = form.text("timestamp_granularities[]", "segment".to_string());
+            }
+        }
 
-    if !resp.status().is_success() {
-        let status = resp.status();
-        let body = resp.text().await.unwrap_or_default();
-        return Err(anyhow::anyhow!("OpenAI error {}: {}", status, body));
-    }
+        let mut request = client.post(&url).multipart(form);
+        if let Some(api_key) = p.api_key.as_deref().filter(|k| !k.is_empty()) {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        for (name, value) in &endpoint.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
 
-    let whisper_response: WhisperResponse = resp.json().await?;
+        let (retry_after, error) = match request.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                let mut whisper_response: WhisperResponse = resp.json().await?;
+                whisper_response.task = Some(p.task.as_str().to_string());
+                return Ok(whisper_response);
+            }
+            Ok(resp) => {
+                let status = resp.status();
+                let retry_after = resp.headers().get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .map(std::time::Duration::from_secs);
+                let body = resp.text().await.unwrap_or_default();
+                let error = anyhow::anyhow!("Transcription API error {}: {}", status, body);
+                if !(status.as_u16() == 429 || status.is_server_error()) {
+                    return Err(error);
+                }
+                (retry_after, error)
+            }
+            Err(e) => (None, anyhow::anyhow!("Transcription API request failed: {}", e)),
+        };
 
-    let segments = whisper_to_caption_segments(&whisper_response, p.split_by_words);
+        if attempt >= API_MAX_ATTEMPTS {
+            return Err(error);
+        }
 
-    // Save to cache
-    if let Err(e) = save_cached_whisper_response(&p.audio, &p, &whisper_response).await {
-        emit(RpcEvent::Log { id: id.into(), message: format!("Failed to cache transcription: {}", e) });
+        // Honor Retry-After, but don't let a proxy park us for minutes
+        let wait = retry_after.unwrap_or(backoff).min(std::time::Duration::from_secs(60));
+        emit(RpcEvent::Log {
+            id: id.into(),
+            message: format!("{} (attempt {}/{}), retrying in {:?}", error, attempt, API_MAX_ATTEMPTS, wait)
+        });
+        tokio::time::sleep(wait).await;
+        backoff *= 2;
     }
-
-    create_transcription_result(id, &segments, &whisper_response, &p, temp_dir).await
 }
 
 
@@ -1270,25 +1425,27 @@ fn is_digits(s: &str) -> bool {
     !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
 }
 
-fn format_with_thousands(digits: String) -> String {
-    // insert commas every 3 from right
+fn format_with_thousands(digits: String, fmt: NumberFormat) -> String {
+    // insert the group separator every 3 from right
     let mut out = String::new();
-    let mut cnt = 0;
-    for ch in digits.chars().rev() {
-        if cnt > 0 && cnt % 3 == 0 { out.push(','); }
+    for (cnt, ch) in digits.chars().rev().enumerate() {
+        if cnt > 0 && cnt % 3 == 0 { out.push(fmt.thousands); }
         out.push(ch);
-        cnt += 1;
     }
     out.chars().rev().collect()
 }
 
 /// Merge currency symbols, thousand-groups, and decimals into single tokens.
 /// Handles patterns like ["$", "225", "000"] → "$225,000" and ["19", ".", "99"] → "19.99"
-/// Returns (text, start_ms, end_ms) tuples ready for CaptionSegment mapping.
+/// (separators follow the transcript language, e.g. "19,99" for German).
+/// Returns (text, start_ms, end_ms, confidence) tuples ready for CaptionSegment mapping.
 fn merge_numbers_and_currency(
     words: &[WhisperWord],
-    max_duration_ms: Option<u64>
-) -> Vec<(String, u64, u64)> {
+    max_duration_ms: Option<u64>,
+    fmt: NumberFormat
+) -> Vec<(String, u64, u64, Option<f32>)> {
+    let is_decimal_point = |t: &str| t == "." || t == fmt.decimal.to_string();
+    let confidence = |merged: &[WhisperWord]| merged.iter().fold(None, |c, w| min_confidence(c, w.confidence));
     let mut out = Vec::new();
     let mut i = 0usize;
 
@@ -1324,21 +1481,21 @@ fn merge_numbers_and_currency(
 
                 // optional decimal part: "." + 1–2 digits
                 if j + 1 < words.len()
-                    && words[j].word.trim() == "."
+                    && is_decimal_point(words[j].word.trim())
                     && is_digits(words[j + 1].word.trim())
                     && words[j + 1].word.trim().len() <= 2
                 {
                     let decimal = words[j + 1].word.trim();
                     end_ms = ((words[j + 1].end * 1000.0) as u64).min(max_duration_ms.unwrap_or(u64::MAX));
-                    let merged = format!("${}.{}", format_with_thousands(groups.join("")), decimal);
-                    out.push((merged, start_ms, end_ms));
+                    let merged = format!("${}{}{}", format_with_thousands(groups.join(""), fmt), fmt.decimal, decimal);
+                    out.push((merged, start_ms, end_ms, confidence(&words[i..j + 2])));
                     i = j + 2;
                     continue;
                 }
 
                 // no decimals
-                let merged = format!("${}", format_with_thousands(groups.join("")));
-                out.push((merged, start_ms, end_ms));
+                let merged = format!("${}", format_with_thousands(groups.join(""), fmt));
+                out.push((merged, start_ms, end_ms, confidence(&words[i..j])));
                 i = j;
                 continue;
             }
@@ -1360,21 +1517,21 @@ fn merge_numbers_and_currency(
 
             // optional decimals
             if j + 1 < words.len()
-                && words[j].word.trim() == "."
+                && is_decimal_point(words[j].word.trim())
                 && is_digits(words[j + 1].word.trim())
                 && words[j + 1].word.trim().len() <= 2
             {
                 let decimal = words[j + 1].word.trim();
                 end_ms = ((words[j + 1].end * 1000.0) as u64).min(max_duration_ms.unwrap_or(u64::MAX));
-                let merged = format!("{}.{}", format_with_thousands(groups.join("")), decimal);
-                out.push((merged, start_ms, end_ms));
+                let merged = format!("{}{}{}", format_with_thousands(groups.join(""), fmt), fmt.decimal, decimal);
+                out.push((merged, start_ms, end_ms, confidence(&words[i..j + 2])));
                 i = j + 2;
                 continue;
             }
 
             if groups.len() > 1 {
-                let merged = format_with_thousands(groups.join(""));
-                out.push((merged, start_ms, end_ms));
+                let merged = format_with_thousands(groups.join(""), fmt);
+                out.push((merged, start_ms, end_ms, confidence(&words[i..j])));
                 i = j;
                 continue;
             }
@@ -1382,7 +1539,7 @@ fn merge_numbers_and_currency(
 
         // Fallback: keep token as-is
         if end_ms > start_ms {
-            out.push((words[i].word.trim().to_string(), start_ms, end_ms));
+            out.push((words[i].word.trim().to_string(), start_ms, end_ms, words[i].confidence));
         }
         i += 1;
     }
@@ -1390,27 +1547,79 @@ fn merge_numbers_and_currency(
     out
 }
 
+// Below this, a word is listed for review
+pub(crate) const LOW_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+// Confidence of a whole segment from its mean token log probability
+fn segment_confidence(seg: &crate::types::WhisperSegment) -> Option<f32> {
+    seg.avg_logprob.map(|lp| lp.exp().clamp(0.0, 1.0) as f32)
+}
+
+// The last segment that started at or before `ms`
+fn segment_at(response: &WhisperResponse, ms: u64) -> Option<&crate::types::WhisperSegment> {
+    response.segments.as_ref()?.iter()
+        .take_while(|seg| (seg.start * 1000.0) as u64 <= ms)
+        .last()
+}
+
+/// Words the recognizer was unsure about, using the same word merging as the captions.
+/// Backends without per-word probabilities (OpenAI) fall back to their segment's confidence.
+pub fn low_confidence_words(response: &WhisperResponse) -> Vec<LowConfidenceWord> {
+    let Some(words) = response.words.as_ref() else { return Vec::new(); };
+    let max_duration_ms = response.duration.map(|d| (d * 1000.0) as u64);
+    let language = if response.task.as_deref() == Some("translate") { Some("en") } else { response.language.as_deref() };
+
+    merge_numbers_and_currency(words, max_duration_ms, number_format_for(language))
+        .into_iter()
+        .filter_map(|(text, start_ms, end_ms, confidence)| {
+            let confidence = confidence.or_else(|| segment_at(response, start_ms).and_then(segment_confidence))?;
+            (confidence < LOW_CONFIDENCE_THRESHOLD && end_ms > start_ms)
+                .then_some(LowConfidenceWord { text, start_ms, end_ms, confidence })
+        })
+        .collect()
+}
+
 pub fn whisper_to_caption_segments(response: &WhisperResponse, split_by_words: bool) -> Vec<CaptionSegment> {
     let max_duration_ms = response.duration.map(|d| (d * 1000.0) as u64);
+    // Translations are always English, whatever was spoken
+    let translated = response.task.as_deref() == Some("translate");
+    let language = if translated { Some("en".to_string()) } else { response.language.clone() };
+    let segment_language = |seg: &crate::types::WhisperSegment| {
+        if translated { language.clone() } else { seg.language.clone().or_else(|| language.clone()) }
+    };
+    // Words take the speaker of the last segment that started at or before them
+    let speaker_at = |ms: u64| segment_at(response, ms).and_then(|seg| seg.speaker);
+    // A segment's confidence is its own, or the mean over the words inside it
+    let confidence_of = |seg: &crate::types::WhisperSegment| {
+        segment_confidence(seg).or_else(|| {
+            let inside: Vec<f32> = response.words.iter().flatten()
+                .filter(|w| w.start >= seg.start && w.start < seg.end)
+                .filter_map(|w| w.confidence)
+                .collect();
+            (!inside.is_empty()).then(|| inside.iter().sum::<f32>() / inside.len() as f32)
+        })
+    };
 
-    if split_by_words && response.words.is_some() {
-        let words = response.words.as_ref().unwrap();
-        let merged = merge_numbers_and_currency(words, max_duration_ms);
+    if let (true, Some(words)) = (split_by_words, response.words.as_ref()) {
+        let merged = merge_numbers_and_currency(words, max_duration_ms, number_format_for(language.as_deref()));
 
         merged.into_iter()
-            .filter_map(|(text, start_ms, end_ms)| {
+            .filter_map(|(text, start_ms, end_ms, confidence)| {
                 if end_ms <= start_ms { return None; }
                 Some(CaptionSegment {
                     start_ms,
                     end_ms,
                     text,
                     words: Vec::new(),
+                    language: language.clone(),
+                    speaker: speaker_at(start_ms),
+                    confidence: confidence.or_else(|| segment_at(response, start_ms).and_then(segment_confidence)),
+                    hidden: false,
                 })
             })
             .collect()
-    } else if split_by_words && response.segments.is_some() {
+    } else if let (true, Some(segments)) = (split_by_words, response.segments.as_ref()) {
         // Auto-split segments into words when word-level timestamps are not available
-        let segments = response.segments.as_ref().unwrap();
         let mut word_segments = Vec::new();
 
         for seg in segments {
@@ -1472,15 +1681,24 @@ pub fn whisper_to_caption_segments(response: &WhisperResponse, split_by_words: b
                     end_ms: word_end_ms,
                     text: word.to_string(),
                     words: Vec::new(),
+                    language: segment_language(seg),
+                    speaker: seg.speaker,
+                    confidence: segment_confidence(seg),
+                    hidden: false,
                 });
             }
         }
 
         word_segments
     } else if let Some(segments) = &response.segments {
-        // use segment-level timing
+        // use segment-level timing, keeping whisper's words (merged the same way as
+        // low_confidence_words) so a review render flags exactly the listed words
+        let merged = response.words.as_ref()
+            .map(|words| merge_numbers_and_currency(words, max_duration_ms, number_format_for(language.as_deref())))
+            .unwrap_or_default();
         segments.iter()
-            .filter_map(|seg| {
+            .enumerate()
+            .filter_map(|(i, seg)| {
                 let start_ms = (seg.start * 1000.0) as u64;
                 let end_ms = (seg.end * 1000.0) as u64;
 
@@ -1503,11 +1721,31 @@ pub fn whisper_to_caption_segments(response: &WhisperResponse, split_by_words: b
                     return None;
                 }
 
+                // A word belongs to the last segment that started at or before it, as in segment_at
+                let next_start_ms = segments.get(i + 1).map(|next| (next.start * 1000.0) as u64);
+                let words = merged.iter()
+                    .filter(|(_, word_start, word_end, _)| {
+                        word_end > word_start && *word_start >= start_ms && next_start_ms.is_none_or(|next| *word_start < next)
+                    })
+                    .map(|(text, word_start, word_end, confidence)| WordSpan {
+                        start_ms: *word_start,
+                        end_ms: *word_end,
+                        text: text.clone(),
+                        speaker: seg.speaker,
+                        confidence: confidence.or_else(|| segment_confidence(seg)),
+                        hidden: false,
+                    })
+                    .collect();
+
                 Some(CaptionSegment {
                     start_ms,
                     end_ms: final_end_ms,
                     text: seg.text.clone(),
-                    words: Vec::new(), // srt-style segments don't include word timing
+                    words,
+                    language: segment_language(seg),
+                    speaker: seg.speaker,
+                    confidence: confidence_of(seg),
+                    hidden: false,
                 })
             })
             .collect()
@@ -1519,109 +1757,20 @@ pub fn whisper_to_caption_segments(response: &WhisperResponse, split_by_words: b
             end_ms: duration as u64,
             text: response.text.clone(),
             words: Vec::new(),
+            language,
+            speaker: None,
+            confidence: None,
+            hidden: false,
         }]
     }
 }
 
 
-pub async fn get_cached_whisper_response(audio_path: &str, params: &TranscribeSegmentsParams) -> anyhow::Result<Option<WhisperResponse>> {
-    let (audio_hash, params_hash) = compute_segments_cache_key(audio_path, params)?;
-    let index = load_cache_index().await?;
-
-    for entry in &index.entries {
-        if entry.audio_hash == audio_hash && entry.params_hash == params_hash {
-            if std::path::Path::new(&entry.response_path).exists() {
-                let content = fs::read_to_string(&entry.response_path).await?;
-                let response: WhisperResponse = serde_json::from_str(&content)?;
-                return Ok(Some(response));
-            }
-        }
-    }
-    Ok(None)
-}
-
-pub async fn save_cached_whisper_response(audio_path: &str, params: &TranscribeSegmentsParams, response: &WhisperResponse) -> anyhow::Result<()> {
-    let (audio_hash, params_hash) = compute_segments_cache_key(audio_path, params)?;
-    let mut index = load_cache_index().await?;
-    let cache_dir = get_cache_dir()?;
-    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
-
-    // create cache filename and save JSON response
-    let cache_filename = format!("{}_{}.json", &audio_hash[..8], &params_hash[..8]);
-    let cached_json_path = cache_dir.join(cache_filename);
-    let json_content = serde_json::to_string_pretty(response)?;
-    fs::write(&cached_json_path, json_content).await?;
-
-    // add new entry
-    let new_entry = WhisperCacheEntry {
-        audio_hash,
-        params_hash,
-        response_path: cached_json_path.to_string_lossy().to_string(),
-        timestamp,
-    };
-
-    // remove old entry if exists
-    index.entries.retain(|e| !(e.audio_hash == new_entry.audio_hash && e.params_hash == new_entry.params_hash));
-
-    // add new entry
-    index.entries.push(new_entry);
-
-    // keep only 4 most recent entries (LRU eviction)
-    if index.entries.len() > 4 {
-        index.entries.sort_by_key(|e| e.timestamp);
-        let to_remove = index.entries.drain(0..index.entries.len() - 4).collect::<Vec<_>>();
-
-        // delete old cached files
-        for entry in to_remove {
-            let _ = fs::remove_file(&entry.response_path).await;
-        }
-    }
-
-    save_cache_index(&index).await?;
-    Ok(())
-}
-
-
-pub fn compute_segments_cache_key(audio_path: &str, params: &TranscribeSegmentsParams) -> anyhow::Result<(String, String)> {
-    // hash audio file content
-    let audio_bytes = std::fs::read(audio_path)?;
-    let audio_hash = blake3::hash(&audio_bytes).to_hex().to_string();
-
-    // hash relevant parameters (excluding video_file as it doesn't affect transcription)
-    let params_for_hash = serde_json::json!({
-        "model": params.model,
-        "language": params.language,
-        "split_by_words": params.split_by_words,
-        "prompt": params.prompt,
-    });
-    let params_hash = blake3::hash(params_for_hash.to_string().as_bytes()).to_hex().to_string();
-
-    Ok((audio_hash, params_hash))
-}
-
-pub async fn save_cache_index(index: &WhisperCacheIndex) -> anyhow::Result<()> {
-    let cache_dir = get_cache_dir()?;
-    let index_path = cache_dir.join("index.json");
-    let content = serde_json::to_string_pretty(index)?;
-    fs::write(index_path, content).await?;
-    Ok(())
-}
-
-pub async fn load_cache_index() -> anyhow::Result<WhisperCacheIndex> {
-    let cache_dir = get_cache_dir()?;
-    let index_path = cache_dir.join("index.json");
-
-    if index_path.exists() {
-        let content = fs::read_to_string(index_path).await?;
-        Ok(serde_json::from_str(&content).unwrap_or(WhisperCacheIndex { entries: Vec::new() }))
-    } else {
-        Ok(WhisperCacheIndex { entries: Vec::new() })
-    }
+/// Hash a file's content with blake3 without loading it into memory
+pub fn hash_file(path: &str) -> std::io::Result<String> {
+    let file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(std::io::BufReader::new(file))?;
+    Ok(hasher.finalize().to_hex().to_string())
 }
 
-pub fn get_cache_dir() -> std::io::Result<PathBuf> {
-    let mut cache_dir = std::env::temp_dir();
-    cache_dir.push("capslap_whisper_cache");
-    std::fs::create_dir_all(&cache_dir)?;
-    Ok(cache_dir)
-}
@@ -0,0 +1,224 @@
+use crate::rpc::RpcEvent;
+use crate::types::{TranscriptionTask, WhisperResponse, WhisperSegment, WhisperWord};
+use std::process::Stdio;
+use tokio::process::Command as TokioCommand;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+/// A single decoded token with its timing and probability
+#[derive(Debug, Clone)]
+pub struct NativeToken {
+    pub id: i32,
+    pub text: String,
+    pub start: f64,   // seconds
+    pub end: f64,     // seconds
+    pub p: f32,       // token probability (0.0 - 1.0)
+}
+
+#[derive(Debug, Clone)]
+pub struct NativeSegment {
+    pub start: f64,   // seconds
+    pub end: f64,     // seconds
+    pub text: String,
+    pub tokens: Vec<NativeToken>,
+}
+
+/// Full in-process transcription result, richer than the CLI JSON round-trip
+#[derive(Debug, Clone)]
+pub struct NativeTranscript {
+    pub task: TranscriptionTask,
+    pub language: Option<String>,
+    pub segments: Vec<NativeSegment>,
+}
+
+impl NativeTranscript {
+    /// Flatten into the same shape the CLI and API backends produce
+    pub fn to_whisper_response(&self) -> WhisperResponse {
+        let mut text = String::new();
+        let mut segments = Vec::new();
+        let mut words = Vec::new();
+        let mut duration = 0.0f64;
+
+        for (i, seg) in self.segments.iter().enumerate() {
+            text.push_str(seg.text.trim());
+            text.push(' ');
+            duration = duration.max(seg.end);
+
+            segments.push(WhisperSegment {
+                id: i as u32,
+                start: seg.start,
+                end: seg.end,
+                text: seg.text.trim().to_string(),
+                language: self.language.clone(),
+                speaker: None,
+                avg_logprob: None,
+            });
+
+            let tokens: Vec<WhisperWord> = seg.tokens.iter()
+                .map(|tok| WhisperWord { word: tok.text.clone(), start: tok.start, end: tok.end, confidence: Some(tok.p) })
+                .collect();
+            words.extend(crate::whisper::merge_subword_tokens(&tokens));
+        }
+
+        WhisperResponse {
+            task: Some(self.task.as_str().to_string()),
+            language: self.language.clone(),
+            duration: Some(duration),
+            text: text.trim().to_string(),
+            segments: Some(segments),
+            words: if words.is_empty() { None } else { Some(words) },
+        }
+    }
+}
+
+/// Decode any audio file to 16 kHz mono f32 PCM (what whisper.cpp expects) via ffmpeg
+async fn decode_pcm_16k_mono(audio_path: &str) -> anyhow::Result<Vec<f32>> {
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let output = TokioCommand::new(ffmpeg_path)
+        .arg("-v").arg("error")
+        .arg("-i").arg(audio_path)
+        .arg("-ar").arg("16000")
+        .arg("-ac").arg("1")
+        .arg("-f").arg("f32le")
+        .arg("-")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!("ffmpeg PCM decode failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+
+    Ok(output.stdout
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Run whisper.cpp in-process on an already-decoded PCM buffer (blocking)
+fn run_full(
+    model_path: &str,
+    pcm: &[f32],
+    language: Option<&str>,
+    task: TranscriptionTask,
+    prompt: Option<&str>,
+    on_progress: impl FnMut(i32) + 'static,
+) -> anyhow::Result<NativeTranscript> {
+    let ctx = WhisperContext::new_with_params(model_path, WhisperContextParameters::default())
+        .map_err(|e| anyhow::anyhow!("Failed to load whisper model {}: {}", model_path, e))?;
+    let mut state = ctx.create_state()
+        .map_err(|e| anyhow::anyhow!("Failed to create whisper state: {}", e))?;
+
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(8);
+
+    // Mirror the CLI flags used by transcribe_with_whisper_cpp
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_n_threads(threads as i32);
+    params.set_token_timestamps(true);
+    params.set_thold_pt(0.01);
+    params.set_max_len(0);
+    params.set_entropy_thold(2.8);
+    params.set_suppress_nst(true);
+    params.set_language(language);
+    params.set_translate(task == TranscriptionTask::Translate);
+    if let Some(prompt) = prompt {
+        params.set_initial_prompt(prompt);
+    }
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    params.set_print_special(false);
+    params.set_progress_callback_safe(on_progress);
+
+    state.full(params, pcm)
+        .map_err(|e| anyhow::anyhow!("whisper.cpp inference failed: {}", e))?;
+
+    let language = state.full_lang_id_from_state().ok()
+        .and_then(whisper_rs::get_lang_str)
+        .map(|s| s.to_string());
+
+    let eot = ctx.token_eot();
+    let n_segments = state.full_n_segments()
+        .map_err(|e| anyhow::anyhow!("Failed to read segments: {}", e))?;
+
+    let mut segments = Vec::with_capacity(n_segments.max(0) as usize);
+    for s in 0..n_segments {
+        // whisper.cpp timestamps are in 10ms units
+        let start = state.full_get_segment_t0(s).unwrap_or(0) as f64 / 100.0;
+        let end = state.full_get_segment_t1(s).unwrap_or(0) as f64 / 100.0;
+        let text = state.full_get_segment_text_lossy(s).unwrap_or_default();
+
+        let n_tokens = state.full_n_tokens(s).unwrap_or(0);
+        let mut tokens = Vec::with_capacity(n_tokens.max(0) as usize);
+        for t in 0..n_tokens {
+            let Ok(data) = state.full_get_token_data(s, t) else { continue; };
+            // Skip special tokens ([_BEG_], timestamps, [_TT_...])
+            if data.id >= eot { continue; }
+            tokens.push(NativeToken {
+                id: data.id,
+                text: state.full_get_token_text_lossy(s, t).unwrap_or_default(),
+                start: data.t0 as f64 / 100.0,
+                end: data.t1 as f64 / 100.0,
+                p: data.p,
+            });
+        }
+
+        segments.push(NativeSegment { start, end, text, tokens });
+    }
+
+    Ok(NativeTranscript { task, language, segments })
+}
+
+/// Transcribe audio with whisper.cpp linked in-process (no bundled binary, no JSON file)
+pub async fn transcribe_native(
+    id: &str,
+    audio_path: &str,
+    model: Option<String>,
+    language: Option<String>,
+    task: TranscriptionTask,
+    prompt: Option<String>,
+    mut emit: impl FnMut(RpcEvent)
+) -> anyhow::Result<NativeTranscript> {
+    let whisper_model = model.unwrap_or_else(|| "tiny".to_string());
+    let (model_path, actual_model) = crate::whisper::ensure_whisper_model(&whisper_model).await?;
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Starting in-process whisper.cpp transcription with model: {} ({})", actual_model, model_path)
+    });
+
+    let pcm = decode_pcm_16k_mono(audio_path).await?;
+    if pcm.is_empty() {
+        return Err(anyhow::anyhow!("Decoded audio is empty: {}", audio_path));
+    }
+
+    // Progress comes from the inference thread; forward it to the async side
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<i32>();
+    let mut task = tokio::task::spawn_blocking(move || {
+        run_full(&model_path, &pcm, language.as_deref(), task, prompt.as_deref(), move |pct| { let _ = tx.send(pct); })
+    });
+
+    // whisper-rs never drops the callback, so wait on the worker rather than channel close
+    let transcript = loop {
+        tokio::select! {
+            Some(pct) = rx.recv() => {
+                emit(RpcEvent::Progress {
+                    id: id.into(),
+                    status: "Transcribing...".into(),
+                    progress: (pct.clamp(0, 100) as f32) / 100.0
+                });
+            }
+            res = &mut task => {
+                break res.map_err(|e| anyhow::anyhow!("whisper.cpp worker panicked: {}", e))??;
+            }
+        }
+    };
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("In-process whisper.cpp transcription completed. Segments: {}, language: {}",
+            transcript.segments.len(), transcript.language.as_deref().unwrap_or("unknown"))
+    });
+
+    Ok(transcript)
+}
@@ -0,0 +1,341 @@
+use crate::rpc::RpcEvent;
+use crate::types::{TranscriptionTask, WhisperResponse, WhisperSegment, WhisperWord};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, LazyLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command as TokioCommand};
+use tokio::sync::Mutex;
+
+// ---- Server supervision tuning ----
+const SERVER_IDLE_TIMEOUT: Duration = Duration::from_secs(300);  // shut down after 5 min without requests
+const SERVER_REAP_INTERVAL: Duration = Duration::from_secs(30);
+const SERVER_STARTUP_TIMEOUT: Duration = Duration::from_secs(120); // large models take a while to load
+
+/// A running whisper-server process with its model loaded
+struct ServerHandle {
+    child: Child,
+    port: u16,
+    activity: Arc<ServerActivity>,
+}
+
+impl ServerHandle {
+    fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Idle means no request is running and none finished within SERVER_IDLE_TIMEOUT
+    fn is_idle(&self) -> bool {
+        self.activity.in_flight.load(Ordering::Acquire) == 0
+            && self.activity.last_used.lock().unwrap_or_else(|e| e.into_inner()).elapsed() > SERVER_IDLE_TIMEOUT
+    }
+
+    fn lease(&self) -> ServerLease {
+        self.activity.in_flight.fetch_add(1, Ordering::AcqRel);
+        ServerLease { port: self.port, activity: self.activity.clone() }
+    }
+}
+
+/// Request bookkeeping shared between a server and the requests using it
+struct ServerActivity {
+    in_flight: AtomicUsize,
+    last_used: std::sync::Mutex<Instant>,
+}
+
+/// Marks a server as busy for as long as a request holds it, so the reaper never
+/// kills a server in the middle of a long /inference call
+struct ServerLease {
+    port: u16,
+    activity: Arc<ServerActivity>,
+}
+
+impl Drop for ServerLease {
+    fn drop(&mut self) {
+        *self.activity.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        self.activity.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+// One warm server per model file
+static SERVERS: LazyLock<Mutex<HashMap<String, ServerHandle>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+// Per-model startup locks, so a slow launch only blocks requests for that same model
+static LAUNCH_LOCKS: LazyLock<Mutex<HashMap<String, Arc<Mutex<()>>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+static REAPER: std::sync::Once = std::sync::Once::new();
+
+/// Find whisper.cpp's whisper-server binary (bundled > project > system)
+pub fn find_whisper_server_binary() -> anyhow::Result<String> {
+    let exe_name = if cfg!(target_os = "windows") { "whisper-server.exe" } else { "whisper-server" };
+
+    if let Ok(exe_path) = std::env::current_exe() {
+        if let Some(exe_dir) = exe_path.parent() {
+            let bundled = [
+                exe_dir.join("bin").join(exe_name),
+                exe_dir.join("bin-win").join(exe_name),
+                exe_dir.join(exe_name),
+            ];
+            if let Some(path) = bundled.iter().find(|p| p.exists()) {
+                return Ok(path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("bin").join(exe_name);
+    if project.exists() {
+        return Ok(project.to_string_lossy().to_string());
+    }
+
+    which::which(exe_name)
+        .map(|p| p.to_string_lossy().to_string())
+        .map_err(|_| anyhow::anyhow!("whisper-server binary not found in any location"))
+}
+
+/// Ask the OS for a free localhost port
+fn pick_free_port() -> anyhow::Result<u16> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+async fn launch_server(id: &str, model_path: &str, emit: &mut impl FnMut(RpcEvent)) -> anyhow::Result<ServerHandle> {
+    let binary = find_whisper_server_binary()?;
+    let port = pick_free_port()?;
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(8);
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("Launching whisper-server on 127.0.0.1:{} with model {}", port, model_path)
+    });
+
+    let mut child = TokioCommand::new(&binary)
+        .arg("-m").arg(model_path)
+        .arg("--host").arg("127.0.0.1")
+        .arg("--port").arg(port.to_string())
+        .arg("-t").arg(threads.to_string())
+        .arg("--word-thold").arg("0.01")    // Same decoding settings as the CLI path
+        .arg("--entropy-thold").arg("2.8")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    // Wait until the model is loaded and the HTTP port accepts connections
+    let deadline = Instant::now() + SERVER_STARTUP_TIMEOUT;
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(anyhow::anyhow!("whisper-server exited during startup with status {}", status));
+        }
+        if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            break;
+        }
+        if Instant::now() > deadline {
+            let _ = child.kill().await;
+            return Err(anyhow::anyhow!("whisper-server did not become ready within {:?}", SERVER_STARTUP_TIMEOUT));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    REAPER.call_once(|| { tokio::spawn(reap_idle_servers()); });
+
+    let activity = Arc::new(ServerActivity { in_flight: AtomicUsize::new(0), last_used: std::sync::Mutex::new(Instant::now()) });
+    Ok(ServerHandle { child, port, activity })
+}
+
+/// Background task: stop servers with no running requests that have been idle longer than SERVER_IDLE_TIMEOUT
+async fn reap_idle_servers() {
+    loop {
+        tokio::time::sleep(SERVER_REAP_INTERVAL).await;
+        let mut servers = SERVERS.lock().await;
+        let idle: Vec<String> = servers.iter()
+            .filter(|(_, h)| h.is_idle())
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in idle {
+            if let Some(mut handle) = servers.remove(&key) {
+                let _ = handle.child.kill().await;
+            }
+        }
+    }
+}
+
+/// Stop every running whisper-server (called when the sidecar shuts down)
+pub async fn shutdown_all_servers() {
+    let mut servers = SERVERS.lock().await;
+    for (_, mut handle) in servers.drain() {
+        let _ = handle.child.kill().await;
+    }
+}
+
+/// Convert audio to 16 kHz mono WAV, the only format whisper-server reads without --convert
+async fn convert_to_wav(audio_path: &str) -> anyhow::Result<String> {
+    let wav_path = format!("{}.16k.wav", audio_path);
+    let ffmpeg_path = crate::whisper::find_ffmpeg_binary().await?;
+    let status = TokioCommand::new(ffmpeg_path)
+        .arg("-y").arg("-v").arg("error")
+        .arg("-i").arg(audio_path)
+        .arg("-ar").arg("16000")
+        .arg("-ac").arg("1")
+        .arg("-c:a").arg("pcm_s16le")
+        .arg(&wav_path)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("ffmpeg WAV conversion failed for {}", audio_path));
+    }
+    Ok(wav_path)
+}
+
+/// Lease on the running server for this model, restarting it if the process died.
+/// SERVERS is never held while a server starts up, only the model's own launch lock.
+async fn acquire_server(id: &str, model_path: &str, actual_model: &str, emit: &mut impl FnMut(RpcEvent)) -> anyhow::Result<ServerLease> {
+    let launch_lock = LAUNCH_LOCKS.lock().await.entry(model_path.to_string()).or_default().clone();
+    let _launching = launch_lock.lock().await;
+
+    {
+        let mut servers = SERVERS.lock().await;
+        if let Some(handle) = servers.get_mut(model_path) {
+            if handle.is_alive() {
+                emit(RpcEvent::Log {
+                    id: id.into(),
+                    message: format!("Reusing warm whisper-server for model {}", actual_model)
+                });
+                return Ok(handle.lease());
+            }
+            servers.remove(model_path);
+            emit(RpcEvent::Log { id: id.into(), message: "whisper-server exited, restarting".into() });
+        }
+    }
+
+    let handle = launch_server(id, model_path, emit).await?;
+    let lease = handle.lease();
+    SERVERS.lock().await.insert(model_path.to_string(), handle);
+    Ok(lease)
+}
+
+/// Transcribe audio through a warm, supervised whisper-server process
+pub async fn transcribe_with_whisper_server(
+    id: &str,
+    audio_path: &str,
+    model: Option<String>,
+    language: Option<String>,
+    task: TranscriptionTask,
+    prompt: Option<String>,
+    mut emit: impl FnMut(RpcEvent)
+) -> anyhow::Result<WhisperResponse> {
+    use reqwest::multipart;
+
+    let whisper_model = model.unwrap_or_else(|| "tiny".to_string());
+    let (model_path, actual_model) = crate::whisper::ensure_whisper_model(&whisper_model).await?;
+
+    // Held until the response is parsed so the reaper leaves the server running
+    let server = acquire_server(id, &model_path, &actual_model, &mut emit).await?;
+
+    let wav_path = convert_to_wav(audio_path).await?;
+    let bytes = tokio::fs::read(&wav_path).await?;
+    let _ = tokio::fs::remove_file(&wav_path).await;
+
+    let mut form = multipart::Form::new()
+        .part("file", multipart::Part::bytes(bytes).file_name("audio.wav").mime_str("audio/wav")?)
+        .text("response_format", "verbose_json")
+        .text("temperature", "0.0");
+    if let Some(lang) = &language {
+        form = form.text("language", lang.clone());
+    }
+    if task == TranscriptionTask::Translate {
+        form = form.text("translate", "true");
+    }
+    if let Some(prompt) = prompt {
+        form = form.text("prompt", prompt);
+    }
+
+    let resp = reqwest::Client::new()
+        .post(format!("http://127.0.0.1:{}/inference", server.port))
+        .multipart(form)
+        .send()
+        .await;
+
+    let resp = match resp {
+        Ok(r) if r.status().is_success() => r,
+        other => {
+            // Treat any transport or server error as a dead server so the next call relaunches it
+            if let Some(mut handle) = SERVERS.lock().await.remove(&model_path) {
+                let _ = handle.child.kill().await;
+            }
+            return Err(match other {
+                Ok(r) => anyhow::anyhow!("whisper-server error {}: {}", r.status(), r.text().await.unwrap_or_default()),
+                Err(e) => anyhow::anyhow!("whisper-server request failed: {}", e),
+            });
+        }
+    };
+
+    let body = resp.text().await?;
+    let mut whisper_response = parse_whisper_server_output(&body)?;
+    whisper_response.task = Some(task.as_str().to_string());
+    drop(server);
+
+    emit(RpcEvent::Log {
+        id: id.into(),
+        message: format!("whisper-server transcription completed. Duration: {:.2}s, Segments: {}",
+            whisper_response.duration.unwrap_or(0.0),
+            whisper_response.segments.as_ref().map(|s| s.len()).unwrap_or(0))
+    });
+
+    Ok(whisper_response)
+}
+
+/// Parse whisper-server verbose_json (OpenAI-like, with words nested inside segments)
+fn parse_whisper_server_output(body: &str) -> anyhow::Result<WhisperResponse> {
+    let json: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(err) = json.get("error").and_then(|e| e.as_str()) {
+        return Err(anyhow::anyhow!("whisper-server error: {}", err));
+    }
+
+    let mut segments = Vec::new();
+    let mut words = Vec::new();
+    let mut duration = json.get("duration").and_then(|d| d.as_f64()).unwrap_or(0.0);
+
+    for (i, seg) in json.get("segments").and_then(|s| s.as_array()).into_iter().flatten().enumerate() {
+        let (Some(start), Some(end), Some(text)) = (
+            seg.get("start").and_then(|v| v.as_f64()),
+            seg.get("end").and_then(|v| v.as_f64()),
+            seg.get("text").and_then(|v| v.as_str()),
+        ) else { continue; };
+
+        duration = duration.max(end);
+        segments.push(WhisperSegment {
+            id: i as u32,
+            start,
+            end,
+            text: text.trim().to_string(),
+            language: None,
+            speaker: None,
+            avg_logprob: seg.get("avg_logprob").and_then(|v| v.as_f64()),
+        });
+
+        // whisper-server reports subword tokens as "words"; rebuild real words from them
+        let tokens: Vec<WhisperWord> = seg.get("words").and_then(|w| w.as_array()).into_iter().flatten()
+            .filter_map(|w| Some(WhisperWord {
+                word: w.get("word")?.as_str()?.to_string(),
+                start: w.get("start")?.as_f64()?,
+                end: w.get("end")?.as_f64()?,
+                confidence: w.get("probability").and_then(|p| p.as_f64()).map(|p| p as f32),
+            }))
+            .collect();
+        words.extend(crate::whisper::merge_subword_tokens(&tokens));
+    }
+
+    let text = json.get("text").and_then(|t| t.as_str()).unwrap_or_default().trim().to_string();
+    if text.is_empty() && segments.is_empty() {
+        return Err(crate::whisper::NoSpeech("whisper-server").into());
+    }
+
+    Ok(WhisperResponse {
+        task: Some("transcribe".to_string()),
+        // Reported as a full name ("english"); normalized by the caller
+        language: json.get("language").and_then(|l| l.as_str()).map(|s| s.to_string()),
+        duration: Some(duration),
+        text,
+        segments: Some(segments),
+        words: if words.is_empty() { None } else { Some(words) },
+    })
+}